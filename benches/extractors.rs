@@ -0,0 +1,74 @@
+#![allow(unused_crate_dependencies, clippy::print_stdout)]
+//! Mean time of one extraction through the [`ExtractFut`](garde_actix_web::web::ExtractFut) of each extractor
+//!
+//! Run with `cargo bench --all-features`, on two revisions to compare them.
+use actix_web::FromRequest;
+use actix_web::dev::Payload;
+use actix_web::test::TestRequest;
+use actix_web::web::Bytes;
+use futures::executor::block_on;
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const WARMUP: Duration = Duration::from_millis(500);
+const MEASURE: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Validate, Serialize, Deserialize)]
+struct User {
+  #[garde(length(min = 3, max = 25))]
+  username: String,
+  #[garde(range(min = 18, max = 128))]
+  age: u8,
+}
+
+fn user() -> User {
+  User {
+    username: "netwo".to_string(),
+    age: 24,
+  }
+}
+
+/// Mean time of `f` over [`MEASURE`], after running it for [`WARMUP`]
+fn measure(mut f: impl FnMut() -> bool) -> Duration {
+  let start = Instant::now();
+  while start.elapsed() < WARMUP {
+    black_box(f());
+  }
+
+  let start = Instant::now();
+  let mut iterations = 0u32;
+  while start.elapsed() < MEASURE {
+    black_box(f());
+    iterations += 1;
+  }
+  start.elapsed() / iterations
+}
+
+/// Extracts `E` from the same request, with a fresh payload holding `body` on each iteration
+fn bench<E: FromRequest>(name: &str, req: TestRequest, body: &[u8]) {
+  let req = req.to_http_request();
+  let body = Bytes::copy_from_slice(body);
+
+  let mean = measure(|| {
+    let mut payload = Payload::from(body.clone());
+    block_on(E::from_request(&req, &mut payload)).is_ok()
+  });
+
+  println!("{name:<10} {mean:>10.2?}");
+}
+
+fn main() {
+  let json = serde_json::to_vec(&user()).unwrap_or_default();
+  let form = serde_urlencoded::to_string(user()).unwrap_or_default();
+
+  bench::<garde_actix_web::web::Json<User>>("json", TestRequest::post().set_json(user()), &json);
+  bench::<garde_actix_web::web::Form<User>>("form", TestRequest::post().set_form(user()), form.as_bytes());
+
+  #[cfg(feature = "serde_qs")]
+  bench::<garde_actix_web::web::QsForm<User>>("qs form", TestRequest::post().set_form(user()), form.as_bytes());
+
+  #[cfg(feature = "lab_query")]
+  bench::<garde_actix_web::web::LabQuery<User>>("lab query", TestRequest::get().uri("/?username=netwo&age=24"), b"");
+}
@@ -0,0 +1,221 @@
+//! Asynchronous validation hooks
+//!
+//! Rules depending on IO (a username not taken yet, a referenced entity existing...) can implement [`AsyncValidate`].
+//! Once an [`AsyncValidator`] is registered through actix's `app_data`, body extractors (`Json`, `Form` and `QsForm`)
+//! run the hook after the synchronous garde validation succeeded. The returned `Report` is handled as any validation error.
+//!
+//! Types without a registered `AsyncValidator` are only validated synchronously.
+use actix_web::HttpRequest;
+use actix_web::web::Data;
+use futures::future::LocalBoxFuture;
+use garde::Report;
+use std::future::Future;
+
+/// Asynchronous validation, run after `garde::Validate`
+///
+/// The request gives access to app data such as a connection pool.
+///
+/// ```rust
+/// use actix_web::HttpRequest;
+/// use garde::{Path, Report, Validate};
+/// use garde_actix_web::async_validate::AsyncValidate;
+///
+/// #[derive(Validate)]
+/// struct NewUser {
+///   #[garde(length(min = 3))]
+///   username: String,
+/// }
+///
+/// impl AsyncValidate for NewUser {
+///   async fn validate_async(&self, _req: &HttpRequest) -> Result<(), Report> {
+///     let mut report = Report::new();
+///     if self.username == "admin" {
+///       report.append(Path::new("username"), garde::Error::new("username is already taken"));
+///     }
+///     if report.is_empty() { Ok(()) } else { Err(report) }
+///   }
+/// }
+/// ```
+pub trait AsyncValidate {
+  fn validate_async(&self, req: &HttpRequest) -> impl Future<Output = Result<(), Report>>;
+}
+
+/// Enables the [`AsyncValidate`] hook of `T`, registered as `app_data`
+///
+/// ```rust
+/// # use actix_web::HttpRequest;
+/// # use garde::{Report, Validate};
+/// # use garde_actix_web::async_validate::AsyncValidate;
+/// use actix_web::App;
+/// use garde_actix_web::async_validate::AsyncValidator;
+///
+/// # #[derive(Validate)]
+/// # struct NewUser {
+/// #   #[garde(skip)]
+/// #   username: String,
+/// # }
+/// # impl AsyncValidate for NewUser {
+/// #   async fn validate_async(&self, _req: &HttpRequest) -> Result<(), Report> { Ok(()) }
+/// # }
+/// let app = App::new().app_data(AsyncValidator::<NewUser>::new());
+/// ```
+pub struct AsyncValidator<T> {
+  validate: for<'a> fn(&'a T, &'a HttpRequest) -> LocalBoxFuture<'a, Result<(), Report>>,
+}
+
+impl<T: AsyncValidate + 'static> AsyncValidator<T> {
+  pub fn new() -> Self {
+    Self {
+      validate: validate_boxed::<T>,
+    }
+  }
+}
+
+impl<T: AsyncValidate + 'static> Default for AsyncValidator<T> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<T> Clone for AsyncValidator<T> {
+  fn clone(&self) -> Self {
+    *self
+  }
+}
+
+impl<T> Copy for AsyncValidator<T> {}
+
+fn validate_boxed<'a, T: AsyncValidate + 'static>(
+  data: &'a T,
+  req: &'a HttpRequest,
+) -> LocalBoxFuture<'a, Result<(), Report>> {
+  Box::pin(data.validate_async(req))
+}
+
+fn async_validator<T: 'static>(req: &HttpRequest) -> Option<&AsyncValidator<T>> {
+  req
+    .app_data::<AsyncValidator<T>>()
+    .or_else(|| req.app_data::<Data<AsyncValidator<T>>>().map(|d| d.as_ref()))
+}
+
+pub(crate) fn has_async_validator<T: 'static>(req: &HttpRequest) -> bool {
+  async_validator::<T>(req).is_some()
+}
+
+/// Runs the registered hook of `T`, if any
+pub(crate) async fn validate_async_for_request<T: 'static>(
+  data: T,
+  req: &HttpRequest,
+) -> Result<T, crate::error::Error> {
+  match check_async_for_request(&data, req).await {
+    None => Ok(data),
+    Some(report) => Err(report.into()),
+  }
+}
+
+/// Runs the registered hook of `T`, if any, handing back the report instead of failing
+pub(crate) async fn check_async_for_request<T: 'static>(data: &T, req: &HttpRequest) -> Option<Report> {
+  match async_validator::<T>(req) {
+    Some(validator) => (validator.validate)(data, req).await.err(),
+    None => None,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::async_validate::{AsyncValidate, AsyncValidator};
+  use crate::web::{Form, Json};
+  use actix_http::StatusCode;
+  use actix_web::test::{TestRequest, call_service, init_service};
+  use actix_web::web::{Data, post, resource};
+  use actix_web::{App, HttpRequest, HttpResponse};
+  use garde::{Path, Report, Validate};
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Debug, PartialEq, Validate, Serialize, Deserialize)]
+  struct UserData {
+    #[garde(length(min = 3))]
+    username: String,
+  }
+
+  struct TakenUsernames(Vec<String>);
+
+  impl AsyncValidate for UserData {
+    async fn validate_async(&self, req: &HttpRequest) -> Result<(), Report> {
+      let taken = req
+        .app_data::<Data<TakenUsernames>>()
+        .map(|d| d.0.contains(&self.username));
+
+      match taken {
+        Some(true) => {
+          let mut report = Report::new();
+          report.append(Path::new("username"), garde::Error::new("username is already taken"));
+          Err(report)
+        }
+        _ => Ok(()),
+      }
+    }
+  }
+
+  async fn test_json_handler(_: Json<UserData>) -> HttpResponse {
+    HttpResponse::Ok().finish()
+  }
+
+  async fn test_form_handler(_: Form<UserData>) -> HttpResponse {
+    HttpResponse::Ok().finish()
+  }
+
+  #[tokio::test]
+  async fn test_async_validation() {
+    let app = init_service(
+      App::new()
+        .app_data(Data::new(TakenUsernames(vec!["netwo".to_string()])))
+        .app_data(AsyncValidator::<UserData>::new())
+        .service(resource("/json").route(post().to(test_json_handler)))
+        .service(resource("/form").route(post().to(test_form_handler))),
+    )
+    .await;
+
+    for uri in ["/json", "/form"] {
+      let data = |username: &str| UserData {
+        username: username.to_string(),
+      };
+      let request = |username: &str| {
+        let req = TestRequest::post().uri(uri);
+        if uri == "/json" {
+          req.set_json(data(username)).to_request()
+        } else {
+          req.set_form(data(username)).to_request()
+        }
+      };
+
+      let resp = call_service(&app, request("garde")).await;
+      assert_eq!(resp.status(), StatusCode::OK);
+
+      let resp = call_service(&app, request("netwo")).await;
+      assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+      let resp = call_service(&app, request("ne")).await;
+      assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+  }
+
+  #[tokio::test]
+  async fn test_async_validation_not_registered() {
+    let app = init_service(
+      App::new()
+        .app_data(Data::new(TakenUsernames(vec!["netwo".to_string()])))
+        .service(resource("/").route(post().to(test_json_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post()
+      .uri("/")
+      .set_json(UserData {
+        username: "netwo".to_string(),
+      })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+  }
+}
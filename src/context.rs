@@ -0,0 +1,318 @@
+//! Request based garde contexts
+//!
+//! A [`Provider`] registered through actix's `app_data` builds the validation context of every extractor from the request,
+//! including the values stored in `req.extensions()` by middlewares.
+//! When it is missing or returns `None`, the context registered as `app_data` or `Data` is used.
+//! Without any of them, the context falls back to [`ContextFallback::fallback`], unless [`GardeConfig::strict_context`](crate::web::GardeConfig::strict_context) is enabled.
+use actix_web::HttpRequest;
+use actix_web::web::Data;
+use std::any::TypeId;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// Builds a garde context from the request
+///
+/// Implemented for every `Fn(&HttpRequest) -> Option<C>` closure.
+pub trait ContextProvider<C> {
+  /// Builds the context, `None` falls back to the context registered in app data
+  fn provide(&self, req: &HttpRequest) -> Option<C>;
+}
+
+impl<C, F> ContextProvider<C> for F
+where
+  F: Fn(&HttpRequest) -> Option<C>,
+{
+  fn provide(&self, req: &HttpRequest) -> Option<C> {
+    (self)(req)
+  }
+}
+
+/// Context used when none is provided for the request
+///
+/// Implemented for every `Default` context. Contexts which must always be supplied can implement it without `Default`,
+/// a missing context is then rejected as a server misconfiguration:
+///
+/// ```rust
+/// use garde_actix_web::context::ContextFallback;
+///
+/// struct TenantLimits {
+///   max_items: usize,
+/// }
+///
+/// impl ContextFallback for TenantLimits {
+///   fn fallback() -> Option<Self> {
+///     None
+///   }
+/// }
+/// ```
+pub trait ContextFallback: Sized {
+  fn fallback() -> Option<Self>;
+}
+
+impl<C: Default> ContextFallback for C {
+  fn fallback() -> Option<Self> {
+    Some(C::default())
+  }
+}
+
+/// [`ContextProvider`] registered as `app_data` for the context `C`
+///
+/// ```rust
+/// use actix_web::{App, HttpMessage, HttpRequest};
+/// use garde_actix_web::context::Provider;
+///
+/// #[derive(Clone)]
+/// struct Tenant {
+///   max_items: usize,
+/// }
+///
+/// struct LimitsContext {
+///   max_items: usize,
+/// }
+///
+/// let app = App::new().app_data(Provider::new(|req: &HttpRequest| {
+///   req
+///     .extensions()
+///     .get::<Tenant>()
+///     .map(|tenant| LimitsContext { max_items: tenant.max_items })
+/// }));
+/// ```
+pub struct Provider<C> {
+  provider: Arc<dyn ContextProvider<C> + Send + Sync>,
+}
+
+impl<C> Provider<C> {
+  pub fn new<P>(provider: P) -> Self
+  where
+    P: ContextProvider<C> + Send + Sync + 'static,
+  {
+    Self {
+      provider: Arc::new(provider),
+    }
+  }
+}
+
+impl<C> Clone for Provider<C> {
+  fn clone(&self) -> Self {
+    Self {
+      provider: self.provider.clone(),
+    }
+  }
+}
+
+impl<C> ContextProvider<C> for Provider<C> {
+  fn provide(&self, req: &HttpRequest) -> Option<C> {
+    self.provider.provide(req)
+  }
+}
+
+pub(crate) enum Resolved<'a, C> {
+  Provided(C),
+  Registered(&'a C),
+}
+
+impl<C> Deref for Resolved<'_, C> {
+  type Target = C;
+
+  fn deref(&self) -> &C {
+    match self {
+      Resolved::Provided(context) => context,
+      Resolved::Registered(context) => context,
+    }
+  }
+}
+
+/// Looks up the context of a request: provider first, then `app_data` and `Data`
+pub(crate) fn resolve<C: 'static>(req: &HttpRequest) -> Option<Resolved<'_, C>> {
+  req
+    .app_data::<Provider<C>>()
+    .or_else(|| req.app_data::<Data<Provider<C>>>().map(|d| d.as_ref()))
+    .and_then(|provider| provider.provide(req))
+    .map(Resolved::Provided)
+    .or_else(|| {
+      req
+        .app_data::<C>()
+        .or_else(|| req.app_data::<Data<C>>().map(|d| d.as_ref()))
+        .map(Resolved::Registered)
+    })
+}
+
+/// Context used when none is found for the request, the unit context of types without custom validation is always available
+pub(crate) fn fallback<C: ContextFallback + 'static>(strict: bool) -> Option<Resolved<'static, C>> {
+  if strict && TypeId::of::<C>() != TypeId::of::<()>() {
+    return None;
+  }
+
+  C::fallback().map(Resolved::Provided)
+}
+
+#[cfg(test)]
+mod test {
+  use crate::context::{ContextFallback, Provider};
+  use crate::web::{GardeConfig, Json, Path, Query};
+  use actix_http::StatusCode;
+  use actix_web::dev::Service;
+  use actix_web::test::{TestRequest, call_service, init_service};
+  use actix_web::web::{post, resource};
+  use actix_web::{App, HttpMessage, HttpRequest, HttpResponse};
+  use garde::Validate;
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Debug, PartialEq, Validate, Serialize, Deserialize)]
+  #[garde(context(NumberContext))]
+  struct AgeData {
+    #[garde(custom(is_big_enough))]
+    age: u8,
+  }
+
+  #[derive(Default, Debug)]
+  struct NumberContext {
+    min: u8,
+  }
+
+  #[derive(Clone)]
+  struct Tenant {
+    min_age: u8,
+  }
+
+  fn is_big_enough(value: &u8, context: &NumberContext) -> garde::Result {
+    if value < &context.min {
+      return Err(garde::Error::new("Number is too low"));
+    }
+    Ok(())
+  }
+
+  #[derive(Debug, PartialEq, Validate, Serialize, Deserialize)]
+  #[garde(context(RequiredContext))]
+  struct RequiredData {
+    #[garde(custom(is_below_max))]
+    age: u8,
+  }
+
+  #[derive(Debug)]
+  struct RequiredContext {
+    max: u8,
+  }
+
+  impl ContextFallback for RequiredContext {
+    fn fallback() -> Option<Self> {
+      None
+    }
+  }
+
+  #[derive(Debug, PartialEq, Validate, Serialize, Deserialize)]
+  struct PlainData {
+    #[garde(range(min = 18, max = 28))]
+    age: u8,
+  }
+
+  fn is_below_max(value: &u8, context: &RequiredContext) -> garde::Result {
+    if value > &context.max {
+      return Err(garde::Error::new("Number is too high"));
+    }
+    Ok(())
+  }
+
+  fn tenant_context(req: &HttpRequest) -> Option<NumberContext> {
+    req
+      .extensions()
+      .get::<Tenant>()
+      .map(|tenant| NumberContext { min: tenant.min_age })
+  }
+
+  async fn test_json_handler(_: Json<AgeData>) -> HttpResponse {
+    HttpResponse::Ok().finish()
+  }
+
+  async fn test_path_handler(_: Path<AgeData>) -> HttpResponse {
+    HttpResponse::Ok().finish()
+  }
+
+  async fn test_required_handler(_: Query<RequiredData>) -> HttpResponse {
+    HttpResponse::Ok().finish()
+  }
+
+  async fn test_plain_handler(_: Query<PlainData>) -> HttpResponse {
+    HttpResponse::Ok().finish()
+  }
+
+  #[tokio::test]
+  async fn test_context_from_request_extensions() {
+    let app = init_service(
+      App::new()
+        .app_data(Provider::new(tenant_context))
+        .app_data(NumberContext { min: 20 })
+        .wrap_fn(|req, srv| {
+          if req.headers().contains_key("x-tenant") {
+            req.extensions_mut().insert(Tenant { min_age: 25 });
+          }
+          srv.call(req)
+        })
+        .service(resource("/").route(post().to(test_json_handler)))
+        .service(resource("/{age}/").route(post().to(test_path_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header(("x-tenant", "netwo"))
+      .set_json(&AgeData { age: 22 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = TestRequest::post().uri("/").set_json(&AgeData { age: 22 }).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = TestRequest::post()
+      .uri("/22/")
+      .insert_header(("x-tenant", "netwo"))
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+  }
+
+  #[tokio::test]
+  async fn test_strict_context() {
+    let app = init_service(
+      App::new()
+        .app_data(GardeConfig::default().strict_context(true))
+        .service(resource("/").route(post().to(test_json_handler)))
+        .service(resource("/plain").route(post().to(test_plain_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post().uri("/").set_json(&AgeData { age: 22 }).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    let req = TestRequest::post().uri("/plain?age=22").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+  }
+
+  #[tokio::test]
+  async fn test_context_without_fallback() {
+    let app = init_service(App::new().service(resource("/").route(post().to(test_required_handler)))).await;
+
+    let req = TestRequest::post().uri("/?age=22").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    let app = init_service(
+      App::new()
+        .app_data(RequiredContext { max: 20 })
+        .service(resource("/").route(post().to(test_required_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post().uri("/?age=22").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = TestRequest::post().uri("/?age=18").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+  }
+}
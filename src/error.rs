@@ -1,14 +1,32 @@
 //! Error exposed by garde-actix-web
 //!
 //! Custom error handlers (provided through the divers configs) should map from an `garde_actix_web::error::Error` to an `actix_web::error::Error`
-use actix_web::error::{JsonPayloadError, PathError, QueryPayloadError, UrlencodedError};
+use actix_web::error::{
+  InternalError, JsonPayloadError, ParseError, PathError, PayloadError, QueryPayloadError, UrlencodedError,
+};
 use actix_web::http::StatusCode;
-use actix_web::{HttpResponse, ResponseError};
+use actix_web::web::Data;
+use actix_web::{HttpRequest, HttpResponse, ResponseError};
 use garde::Report;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::fmt;
 use thiserror::Error;
 
+/// Error raised by an extractor, recording the extractor it originates from
+///
+/// [`Error::as_cause`] tells what failed, [`Error::origin`] which extractor failed.
 #[derive(Debug, Error)]
-pub enum Error {
+#[error("{cause}")]
+pub struct Error {
+  cause: ErrorCause,
+  origin: Option<Source>,
+}
+
+/// Failure wrapped by an [`Error`](struct@Error)
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ErrorCause {
   #[error("Validation error: {0}")]
   ValidationError(Report),
   #[error("Payload error: {0}")]
@@ -19,43 +37,462 @@ pub enum Error {
   PathError(PathError),
   #[error("Urlencoded error: {0}")]
   UrlencodedError(UrlencodedError),
+  #[error("Header error: {0}")]
+  HeaderError(ParseError),
+  #[error("Payload error: {0}")]
+  PayloadError(PayloadError),
   #[cfg(feature = "serde_qs")]
   #[error("Query error: {0}")]
   QsError(serde_qs::Error),
+  #[error("Missing validation context: {0}")]
+  MissingContext(&'static str),
+  #[error("Limit error: nested deeper than {0} levels")]
+  DepthLimit(usize),
+  #[error("Limit error: collection longer than {0} entries")]
+  CollectionLengthLimit(usize),
+  #[error("Limit error: string longer than {0} bytes")]
+  StringLengthLimit(usize),
+  #[error("Limit error: more than {0} fields")]
+  FieldCountLimit(usize),
+  #[error("Duplicate key: {0}")]
+  DuplicateKey(String),
+  /// The [`Body`](crate::web::Body) extractor reads no format for the request `Content-Type`
+  #[error("Unsupported content type")]
+  UnsupportedContentType,
+  /// No branch of an [`Either`](crate::web::Either) could deserialize the payload, errors are in branch order
+  #[error("Either error: {}", join_errors(.0))]
+  EitherError(Vec<Error>),
 }
 
-impl From<Report> for Error {
+impl Error {
+  /// Error raised by the `origin` extractor
+  pub fn new(cause: impl Into<ErrorCause>, origin: Source) -> Self {
+    Error {
+      cause: cause.into(),
+      origin: Some(origin),
+    }
+  }
+
+  /// Failure of the extraction, to match on as the `Error` enum of 0.12
+  pub fn as_cause(&self) -> &ErrorCause {
+    &self.cause
+  }
+
+  /// Unwraps the failure, dropping the extractor it originates from
+  pub fn into_cause(self) -> ErrorCause {
+    self.cause
+  }
+
+  /// Lists the failures as JSON objects holding the field `path`, the `message` and the extractor `source`
+  ///
+  /// Every entry of a validation `Report` becomes an object, other errors are rendered as a single entry with an empty path.
+  pub fn to_json(&self) -> Value {
+    json!({ "errors": self.entries() })
+  }
+
+  /// Builds an `application/json` response from [`Error::to_json`]
+  pub fn json_response(&self, status: StatusCode) -> HttpResponse {
+    HttpResponse::build(status).json(self.to_json())
+  }
+
+  /// Renders the error as an [RFC 9457](https://www.rfc-editor.org/rfc/rfc9457) problem document
+  ///
+  /// Validation failures add an `errors` extension member listing every entry of the `Report`, as do the branch
+  /// errors of [`ErrorCause::EitherError`]. With the `about:blank` type, the title is the HTTP status phrase.
+  pub fn to_problem(&self, status: StatusCode, config: &ProblemConfig, req: &HttpRequest) -> Value {
+    let kind = self.kind();
+    let (type_uri, title) = match config.type_uri_for(kind) {
+      Some(type_uri) => (type_uri, kind.title()),
+      None => ("about:blank", status.canonical_reason().unwrap_or_else(|| kind.title())),
+    };
+    let mut problem = json!({
+      "type": type_uri,
+      "title": title,
+      "status": status.as_u16(),
+      "detail": self.detail(),
+    });
+
+    if config.instance {
+      problem["instance"] = Value::from(req.path());
+    }
+    if let ErrorCause::ValidationError(_) | ErrorCause::EitherError(_) = &self.cause {
+      problem["errors"] = Value::from(self.entries());
+    }
+
+    problem
+  }
+
+  /// Builds an `application/problem+json` response from [`Error::to_problem`]
+  pub fn problem_response(&self, status: StatusCode, config: &ProblemConfig, req: &HttpRequest) -> HttpResponse {
+    HttpResponse::build(status)
+      .content_type("application/problem+json")
+      .body(self.to_problem(status, config, req).to_string())
+  }
+
+  /// Whether the payload could not be deserialized, as opposed to size, content type or validation failures
+  pub fn is_deserialization(&self) -> bool {
+    match &self.cause {
+      ErrorCause::ValidationError(_) => false,
+      ErrorCause::JsonPayloadError(e) => matches!(e, JsonPayloadError::Deserialize(_)),
+      ErrorCause::QueryPayloadError(e) => matches!(e, QueryPayloadError::Deserialize(_)),
+      ErrorCause::PathError(e) => matches!(e, PathError::Deserialize(_)),
+      ErrorCause::UrlencodedError(e) => matches!(e, UrlencodedError::Parse(_)),
+      ErrorCause::HeaderError(_) => true,
+      ErrorCause::PayloadError(_) => false,
+      #[cfg(feature = "serde_qs")]
+      ErrorCause::QsError(_) => true,
+      ErrorCause::MissingContext(_) => false,
+      ErrorCause::DepthLimit(_)
+      | ErrorCause::CollectionLengthLimit(_)
+      | ErrorCause::StringLengthLimit(_)
+      | ErrorCause::FieldCountLimit(_)
+      | ErrorCause::DuplicateKey(_) => false,
+      ErrorCause::UnsupportedContentType => false,
+      ErrorCause::EitherError(errors) => errors.iter().any(Error::is_deserialization),
+    }
+  }
+
+  /// Extractor the error was raised by, only unknown for errors built from a bare [`ErrorCause`]
+  pub fn origin(&self) -> Option<Source> {
+    self.origin
+  }
+
+  /// Part of the request the error comes from, see [`Error::origin`]
+  pub fn location(&self) -> Option<Location> {
+    self.origin.map(|source| source.location())
+  }
+
+  /// Records the extractor the error was raised by, unless already known
+  pub fn with_origin(mut self, origin: Source) -> Self {
+    self.origin.get_or_insert(origin);
+    self
+  }
+
+  /// Kind of the cause, used to pick the problem document `type` and `title`
+  pub fn kind(&self) -> ErrorKind {
+    match &self.cause {
+      ErrorCause::ValidationError(_) => ErrorKind::Validation,
+      ErrorCause::JsonPayloadError(_) => ErrorKind::JsonPayload,
+      ErrorCause::QueryPayloadError(_) => ErrorKind::QueryPayload,
+      ErrorCause::PathError(_) => ErrorKind::Path,
+      ErrorCause::UrlencodedError(_) => ErrorKind::Urlencoded,
+      ErrorCause::HeaderError(_) => ErrorKind::Header,
+      ErrorCause::PayloadError(_) => ErrorKind::Payload,
+      #[cfg(feature = "serde_qs")]
+      ErrorCause::QsError(_) => ErrorKind::Qs,
+      ErrorCause::MissingContext(_) => ErrorKind::MissingContext,
+      ErrorCause::DepthLimit(_) => ErrorKind::DepthLimit,
+      ErrorCause::CollectionLengthLimit(_) => ErrorKind::CollectionLengthLimit,
+      ErrorCause::StringLengthLimit(_) => ErrorKind::StringLengthLimit,
+      ErrorCause::FieldCountLimit(_) => ErrorKind::FieldCountLimit,
+      ErrorCause::DuplicateKey(_) => ErrorKind::DuplicateKey,
+      ErrorCause::UnsupportedContentType => ErrorKind::ContentType,
+      ErrorCause::EitherError(_) => ErrorKind::Either,
+    }
+  }
+
+  fn detail(&self) -> String {
+    match &self.cause {
+      ErrorCause::ValidationError(e) => e.to_string(),
+      ErrorCause::JsonPayloadError(e) => e.to_string(),
+      ErrorCause::QueryPayloadError(e) => e.to_string(),
+      ErrorCause::PathError(e) => e.to_string(),
+      ErrorCause::UrlencodedError(e) => e.to_string(),
+      ErrorCause::HeaderError(e) => e.to_string(),
+      ErrorCause::PayloadError(e) => e.to_string(),
+      #[cfg(feature = "serde_qs")]
+      ErrorCause::QsError(e) => e.to_string(),
+      ErrorCause::MissingContext(_) => "The server is missing the context required to validate the request".to_string(),
+      ErrorCause::DepthLimit(max) => format!("The payload is nested deeper than {max} levels"),
+      ErrorCause::CollectionLengthLimit(max) => format!("A collection of the payload holds more than {max} entries"),
+      ErrorCause::StringLengthLimit(max) => format!("A string of the payload is longer than {max} bytes"),
+      ErrorCause::FieldCountLimit(max) => format!("The payload holds more than {max} fields"),
+      ErrorCause::DuplicateKey(key) => format!("The key `{key}` appears more than once"),
+      ErrorCause::UnsupportedContentType => "The request content type is not supported".to_string(),
+      ErrorCause::EitherError(_) => "No extractor could deserialize the payload".to_string(),
+    }
+  }
+
+  /// Entries of [`Error::to_json`], the entries of each branch for [`ErrorCause::EitherError`]
+  fn entries(&self) -> Vec<Value> {
+    let source = self.origin.map(|s| s.as_str());
+    match &self.cause {
+      ErrorCause::ValidationError(report) => report
+        .iter()
+        .map(|(path, error)| json!({ "path": path.to_string(), "message": error.message(), "source": source }))
+        .collect(),
+      ErrorCause::EitherError(errors) => errors.iter().flat_map(Error::entries).collect(),
+      _ => vec![json!({ "path": "", "message": self.to_string(), "source": source })],
+    }
+  }
+
+  /// Whether the extractor does not read this content type, other branches of an `Either` explain the failure better
+  fn is_content_type(&self) -> bool {
+    matches!(
+      self.cause,
+      ErrorCause::JsonPayloadError(JsonPayloadError::ContentType)
+        | ErrorCause::UrlencodedError(UrlencodedError::ContentType)
+        | ErrorCause::UnsupportedContentType
+    )
+  }
+}
+
+fn join_errors(errors: &[Error]) -> String {
+  let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+  errors.join("\n")
+}
+
+/// Kind of an [`Error`](struct@Error), one per [`ErrorCause`] variant
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+  Validation,
+  JsonPayload,
+  QueryPayload,
+  Path,
+  Urlencoded,
+  Header,
+  Payload,
+  #[cfg(feature = "serde_qs")]
+  Qs,
+  MissingContext,
+  DepthLimit,
+  CollectionLengthLimit,
+  StringLengthLimit,
+  FieldCountLimit,
+  DuplicateKey,
+  ContentType,
+  Either,
+}
+
+impl ErrorKind {
+  /// Short summary used as problem document `title`, unless the `type` is `about:blank`
+  pub fn title(&self) -> &'static str {
+    match self {
+      ErrorKind::Validation => "Validation error",
+      ErrorKind::JsonPayload => "Invalid JSON payload",
+      ErrorKind::QueryPayload => "Invalid query string",
+      ErrorKind::Path => "Invalid path",
+      ErrorKind::Urlencoded => "Invalid urlencoded payload",
+      ErrorKind::Header => "Invalid header",
+      ErrorKind::Payload => "Invalid payload",
+      #[cfg(feature = "serde_qs")]
+      ErrorKind::Qs => "Invalid query string",
+      ErrorKind::MissingContext => "Missing validation context",
+      ErrorKind::DepthLimit => "Payload too deeply nested",
+      ErrorKind::CollectionLengthLimit => "Collection too long",
+      ErrorKind::StringLengthLimit => "String too long",
+      ErrorKind::FieldCountLimit => "Too many fields",
+      ErrorKind::DuplicateKey => "Duplicate key",
+      ErrorKind::ContentType => "Unsupported content type",
+      ErrorKind::Either => "Unsupported payload",
+    }
+  }
+}
+
+/// Settings of the `application/problem+json` error format
+///
+/// Problem `type` defaults to `about:blank`, the request path is only exposed as `instance` once enabled.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProblemConfig {
+  type_uris: HashMap<ErrorKind, String>,
+  default_type_uri: Option<String>,
+  instance: bool,
+}
+
+impl ProblemConfig {
+  /// Sets the `type` URI used for a given kind of error
+  pub fn type_uri(mut self, kind: ErrorKind, uri: impl Into<String>) -> Self {
+    self.type_uris.insert(kind, uri.into());
+    self
+  }
+
+  /// Sets the `type` URI used for kinds without a dedicated one, instead of `about:blank`
+  pub fn default_type_uri(mut self, uri: impl Into<String>) -> Self {
+    self.default_type_uri = Some(uri.into());
+    self
+  }
+
+  /// Exposes the request path as problem `instance`
+  pub fn instance(mut self, instance: bool) -> Self {
+    self.instance = instance;
+    self
+  }
+
+  fn type_uri_for(&self, kind: ErrorKind) -> Option<&str> {
+    self
+      .type_uris
+      .get(&kind)
+      .or(self.default_type_uri.as_ref())
+      .map(String::as_str)
+  }
+}
+
+/// Extractor an error originates from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Source {
+  Path,
+  Query,
+  Json,
+  Form,
+  Header,
+  QsQuery,
+  QsForm,
+  LabQuery,
+  /// Body buffered by [`Either`](crate::web::Either), before any branch ran
+  Either,
+  /// Content type dispatched [`Body`](crate::web::Body), when no format matches
+  Body,
+  /// Any other extractor, wrapped in [`Validated`](crate::web::Validated)
+  Extractor,
+}
+
+impl Source {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Source::Path => "path",
+      Source::Query => "query",
+      Source::Json => "json",
+      Source::Form => "form",
+      Source::Header => "header",
+      Source::QsQuery => "qs_query",
+      Source::QsForm => "qs_form",
+      Source::LabQuery => "lab_query",
+      Source::Either => "either",
+      Source::Body => "body",
+      Source::Extractor => "extractor",
+    }
+  }
+
+  /// Part of the request read by the extractor
+  pub fn location(&self) -> Location {
+    match self {
+      Source::Path => Location::Path,
+      Source::Query | Source::QsQuery | Source::LabQuery => Location::Query,
+      Source::Json | Source::Form | Source::QsForm | Source::Either | Source::Body => Location::Body,
+      Source::Header => Location::Header,
+      Source::Extractor => Location::Request,
+    }
+  }
+}
+
+/// Part of the request an extractor reads
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Location {
+  Path,
+  Query,
+  Body,
+  Header,
+  /// Not known to this crate, e.g. request extensions
+  Request,
+}
+
+impl fmt::Display for Source {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+/// Body format of the responses built when no custom error handler is configured
+///
+/// Register it through actix's `app_data` to switch every extractor at once:
+///
+/// ```rust
+/// use actix_web::App;
+/// use garde_actix_web::error::ErrorFormat;
+///
+/// let app = App::new().app_data(ErrorFormat::Json);
+/// ```
+///
+/// The format set through [`GardeConfig::error_format`](crate::web::GardeConfig::error_format) takes precedence.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ErrorFormat {
+  /// Plain text body built from the error `Display` implementation
+  #[default]
+  Text,
+  /// JSON body built from [`Error::to_json`]
+  Json,
+  /// `application/problem+json` body built from [`Error::to_problem`]
+  Problem(ProblemConfig),
+}
+
+impl ErrorFormat {
+  pub(crate) fn from_req(req: &HttpRequest) -> &Self {
+    req
+      .app_data::<Self>()
+      .or_else(|| req.app_data::<Data<Self>>().map(|d| d.as_ref()))
+      .unwrap_or(&ErrorFormat::Text)
+  }
+
+  pub(crate) fn respond(&self, error: Error, req: &HttpRequest, status: StatusCode) -> actix_web::Error {
+    match self {
+      ErrorFormat::Json => {
+        let response = error.json_response(status);
+        InternalError::from_response(error, response).into()
+      }
+      ErrorFormat::Problem(config) => {
+        let response = error.problem_response(status, config, req);
+        InternalError::from_response(error, response).into()
+      }
+      ErrorFormat::Text if status == error.status_code() => error.into(),
+      ErrorFormat::Text => InternalError::new(error, status).into(),
+    }
+  }
+}
+
+impl<E: Into<ErrorCause>> From<E> for Error {
+  fn from(cause: E) -> Self {
+    Error {
+      cause: cause.into(),
+      origin: None,
+    }
+  }
+}
+
+impl From<Report> for ErrorCause {
   fn from(error: Report) -> Self {
     Self::ValidationError(error)
   }
 }
 
-impl From<JsonPayloadError> for Error {
+impl From<JsonPayloadError> for ErrorCause {
   fn from(error: JsonPayloadError) -> Self {
     Self::JsonPayloadError(error)
   }
 }
 
-impl From<QueryPayloadError> for Error {
+impl From<QueryPayloadError> for ErrorCause {
   fn from(error: QueryPayloadError) -> Self {
     Self::QueryPayloadError(error)
   }
 }
 
-impl From<PathError> for Error {
+impl From<PathError> for ErrorCause {
   fn from(error: PathError) -> Self {
     Self::PathError(error)
   }
 }
 
-impl From<UrlencodedError> for Error {
+impl From<UrlencodedError> for ErrorCause {
   fn from(error: UrlencodedError) -> Self {
     Self::UrlencodedError(error)
   }
 }
 
+impl From<PayloadError> for ErrorCause {
+  fn from(error: PayloadError) -> Self {
+    Self::PayloadError(error)
+  }
+}
+
+impl From<ParseError> for ErrorCause {
+  fn from(error: ParseError) -> Self {
+    Self::HeaderError(error)
+  }
+}
+
 #[cfg(feature = "serde_qs")]
-impl From<serde_qs::Error> for Error {
+impl From<serde_qs::Error> for ErrorCause {
   fn from(error: serde_qs::Error) -> Self {
     Self::QsError(error)
   }
@@ -63,14 +500,28 @@ impl From<serde_qs::Error> for Error {
 
 impl ResponseError for Error {
   fn status_code(&self) -> StatusCode {
-    match self {
-      Error::ValidationError(_) => StatusCode::BAD_REQUEST,
-      Error::JsonPayloadError(e) => e.status_code(),
-      Error::QueryPayloadError(e) => e.status_code(),
-      Error::PathError(e) => e.status_code(),
-      Error::UrlencodedError(e) => e.status_code(),
+    match &self.cause {
+      ErrorCause::ValidationError(_) => StatusCode::BAD_REQUEST,
+      ErrorCause::JsonPayloadError(e) => e.status_code(),
+      ErrorCause::QueryPayloadError(e) => e.status_code(),
+      ErrorCause::PathError(e) => e.status_code(),
+      ErrorCause::UrlencodedError(e) => e.status_code(),
+      ErrorCause::HeaderError(e) => e.status_code(),
+      ErrorCause::PayloadError(e) => e.status_code(),
       #[cfg(feature = "serde_qs")]
-      Error::QsError(_) => StatusCode::BAD_REQUEST,
+      ErrorCause::QsError(_) => StatusCode::BAD_REQUEST,
+      ErrorCause::MissingContext(_) => StatusCode::INTERNAL_SERVER_ERROR,
+      ErrorCause::DepthLimit(_)
+      | ErrorCause::CollectionLengthLimit(_)
+      | ErrorCause::StringLengthLimit(_)
+      | ErrorCause::FieldCountLimit(_)
+      | ErrorCause::DuplicateKey(_) => StatusCode::BAD_REQUEST,
+      ErrorCause::UnsupportedContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+      ErrorCause::EitherError(errors) => errors
+        .iter()
+        .find(|e| !e.is_content_type())
+        .or_else(|| errors.first())
+        .map_or(StatusCode::BAD_REQUEST, ResponseError::status_code),
     }
   }
 
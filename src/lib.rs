@@ -5,7 +5,7 @@
 //! ```toml
 //! [dependencies]
 //! garde = "0.22"
-//! garde-actix-web = "0.12"
+//! garde-actix-web = "0.13"
 //! ```
 //!
 //! # Usage example
@@ -17,12 +17,23 @@
 //! If the payload is invalid, a 400 error is returned (404 for Path).
 //!
 //! Custom error handling can be implemented with an extractor config (`garde_actix_web::web::QueryConfig` in place of `actix_web::web::QueryConfig` for example).
+//! `garde_actix_web::web::HeaderConfig` does the same for headers, including missing and malformed ones.
+//!
+//! Handlers can also implement `garde_actix_web::web::ValidationErrorHandler` to receive the failing extractor kind and type along with the configs in effect, and be chained with fallbacks.
+//!
+//! Errors record the extractor that raised them, available to handlers through `Error::origin` and `Error::location`, while `Error::as_cause` tells what failed.
+//!
+//! An app wide `garde_actix_web::web::GardeConfig` registered as `app_data` provides a shared error handler, status codes and error format to every extractor whose config has no handler.
+//!
+//! `garde_actix_web::error::ErrorFormat::Json` renders errors as a JSON list of `path`, `message` and `source` objects,
+//! `ErrorFormat::Problem` renders [RFC 9457](https://www.rfc-editor.org/rfc/rfc9457) `application/problem+json` documents.
+//! The format is registered as `app_data`, or through `GardeConfig::error_format` which takes precedence.
 //!
 //! ```rust
 //! use actix_web::HttpResponse;
 //! // instead of actix_web::web::Path
 //! use garde_actix_web::web::Path;
-//! use garde::Validate;
+//! use garde::{Report, Validate};
 //!
 //! #[derive(Validate)]
 //! struct MyStruct<'a> {
@@ -35,13 +46,35 @@
 //! }
 //! ```
 //!
+//! Contexts can also be built from the request, see `garde_actix_web::context::Provider`.
+//!
+//! Rules requiring IO can be implemented with `garde_actix_web::async_validate::AsyncValidate`, run by body extractors once registered with an `AsyncValidator`.
+//!
+//! `garde_actix_web::web::Checked<Form<T>>` hands the validation report to the handler instead of rejecting the request.
+//!
+//! Any other extractor dereferencing to a `Validate` type can be validated with `garde_actix_web::web::Validated<E>`.
+//!
+//! `garde_actix_web::web::Valid<Json<T>>` yields a `garde::Valid<T>`, proving at the type level that the data was validated.
+//!
+//! `garde_actix_web::web::Body<T>` picks the JSON or form deserializer from the `Content-Type` header.
+//!
+//! `garde_actix_web::web::ValidatedJson<T>` validates values returned by handlers before serializing them.
+//!
+//! Structs borrowing from the path or the query string are validated without copies by `garde_actix_web::web::PathRef<T>` and `QueryRef<T>`, see `garde_actix_web::web::Borrowed`.
+//! `garde_actix_web::web::JsonBytes<T>` does the same for JSON bodies, keeping the buffered `Bytes` available to the handler.
+//!
+//! `QsForm` bodies are limited to 16kB and must be `application/x-www-form-urlencoded`, see `QsQueryConfig::limit` and `QsQueryConfig::content_type`.
+//! Structural limits (nesting depth, collection and string lengths, field count, duplicate keys) are checked during deserialization once configured, see `garde_actix_web::limits::Limits`.
+//!
 //! ⚠️ When using `garde` [custom validation](https://github.com/jprochazk/garde#custom-validation), the `Context` type needs to implement `Default` which is not required by `garde`.
+//! Contexts which must always be supplied can implement `garde_actix_web::context::ContextFallback` instead, and `GardeConfig::strict_context` rejects every missing context with a 500.
 //!
 //! # Feature flags
 //!
 //! | name       | description                                                   | extra dependencies                                                                           |
 //! |------------|---------------------------------------------------------------|----------------------------------------------------------------------------------------------|
 //! | `serde_qs` | Enables the usage of `garde` for `serde_qs::actix::QsQuery<T>` | [`serde_qs`](https://crates.io/crates/serde_qs)                                      |
+//! | `simd_json` | Adds the `simd-json` backend to `JsonConfig::backend` | [`simd-json`](https://crates.io/crates/simd-json)                                  |
 //!
 //! # Compatibility matrix
 //!
@@ -58,27 +91,62 @@
 //! | `0.20`        | `0.13`           | `0.10.x`                |
 //! | `0.22`        | `0.13`           | `0.11.x`                |
 //! | `0.22`        | `0.15`           | `0.12.x`                |
+//! | `0.22`        | `0.15`           | `0.13.x`                |
 
 #![forbid(unsafe_code)]
 
 use actix_web::HttpRequest;
-use actix_web::web::Data;
-use garde::Validate;
+use context::ContextFallback;
+use error::{ErrorCause, Source};
+use garde::{Report, Validate};
+use std::any::type_name;
+use web::GardeConfig;
 
+pub mod async_validate;
+pub mod context;
 pub mod error;
+pub mod limits;
 pub mod web;
 
-fn validate_for_request<T>(data: T, req: &HttpRequest) -> Result<T, error::Error>
+fn validate_for_request<T>(data: T, req: &HttpRequest, source: Source) -> Result<T, error::Error>
+where
+  T: Validate + 'static,
+  T::Context: ContextFallback,
+{
+  match check_for_request(&data, req, source)? {
+    None => Ok(data),
+    Some(report) => Err(error::Error::new(report, source)),
+  }
+}
+
+/// Validates the data, handing back the report instead of failing, only a missing context is an error
+fn check_for_request<T>(data: &T, req: &HttpRequest, source: Source) -> Result<Option<Report>, error::Error>
 where
   T: Validate + 'static,
-  T::Context: Default,
+  T::Context: ContextFallback,
 {
-  let context = req
-    .app_data::<T::Context>()
-    .or_else(|| req.app_data::<Data<T::Context>>().map(|d| d.as_ref()));
+  let context = context_for_request::<T>(req, source)?;
+
+  Ok(data.validate_with(&context).err())
+}
 
-  match context {
-    None => data.validate().map(|_| data).map_err(Into::into),
-    Some(ctx) => data.validate_with(ctx).map(|_| data).map_err(Into::into),
+/// Context used to validate `T`, only missing in strict mode or for contexts without fallback
+fn context_for_request<T>(req: &HttpRequest, source: Source) -> Result<context::Resolved<'_, T::Context>, error::Error>
+where
+  T: Validate + 'static,
+  T::Context: ContextFallback,
+{
+  match context::resolve::<T::Context>(req) {
+    Some(context) => Ok(context),
+    None => context::fallback(GardeConfig::from_req(req).is_strict_context()).ok_or_else(|| {
+      log::error!(
+        "Missing garde context {} to validate {}. \
+                 Request path: {}",
+        type_name::<T::Context>(),
+        type_name::<T>(),
+        req.path()
+      );
+      error::Error::new(ErrorCause::MissingContext(type_name::<T::Context>()), source)
+    }),
   }
 }
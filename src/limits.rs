@@ -0,0 +1,629 @@
+//! Structural limits checked while deserializing payloads
+//!
+//! Byte size limits do not stop small but abusive payloads: deeply nested JSON, huge arrays, very long strings,
+//! duplicate keys or thousands of parameters. [`Limits`] are set on the extractor configs (`JsonConfig::limits`,
+//! `FormConfig::limits`, `QueryConfig::limits` and `QsQueryConfig::limits`) and checked as the payload is walked by
+//! the deserializer, which stops at the first violation. Each limit is reported with its own [`ErrorCause`] variant.
+//!
+//! ```rust
+//! use actix_web::App;
+//! use garde_actix_web::limits::Limits;
+//! use garde_actix_web::web::{JsonConfig, QueryConfig};
+//!
+//! let limits = Limits::default()
+//!   .max_depth(8)
+//!   .max_collection_length(100)
+//!   .max_string_length(4096)
+//!   .max_fields(500)
+//!   .reject_duplicate_keys(true);
+//!
+//! let app = App::new()
+//!   .app_data(JsonConfig::default().limits(limits))
+//!   .app_data(QueryConfig::default().limits(limits.max_fields(20)));
+//! ```
+use crate::error::{Error, ErrorCause};
+use serde::de::{self, DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor};
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::fmt;
+
+/// Structural limits of a payload, none are enforced by default
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Limits {
+  max_depth: Option<usize>,
+  max_collection_length: Option<usize>,
+  max_string_length: Option<usize>,
+  max_fields: Option<usize>,
+  reject_duplicate_keys: bool,
+}
+
+impl Limits {
+  pub(crate) const NONE: Limits = Limits {
+    max_depth: None,
+    max_collection_length: None,
+    max_string_length: None,
+    max_fields: None,
+    reject_duplicate_keys: false,
+  };
+
+  /// Maximum nesting of sequences and maps, the top level struct being at depth 1
+  pub fn max_depth(mut self, depth: usize) -> Self {
+    self.max_depth = Some(depth);
+    self
+  }
+
+  /// Maximum number of entries of a single sequence or map
+  pub fn max_collection_length(mut self, length: usize) -> Self {
+    self.max_collection_length = Some(length);
+    self
+  }
+
+  /// Maximum length in bytes of string values and keys
+  pub fn max_string_length(mut self, length: usize) -> Self {
+    self.max_string_length = Some(length);
+    self
+  }
+
+  /// Maximum number of map entries and struct fields in the whole payload, e.g. query parameters
+  pub fn max_fields(mut self, fields: usize) -> Self {
+    self.max_fields = Some(fields);
+    self
+  }
+
+  /// Rejects maps and structs holding the same key twice, instead of keeping one of the values
+  ///
+  /// Keys are compared as written, whether strings, numbers, booleans, chars or bytes. Keys of compound types, such
+  /// as tuples, are not checked.
+  pub fn reject_duplicate_keys(mut self, reject: bool) -> Self {
+    self.reject_duplicate_keys = reject;
+    self
+  }
+
+  /// Checks the keys of a query string before `serde_qs` builds its tree from it: field count, bracket nesting and
+  /// duplicate keys, which `serde_qs` would otherwise merge into its own error. Encoded brackets only nest keys out
+  /// of strict mode, and `key[]` keys push onto a sequence instead of repeating the key.
+  #[cfg(feature = "serde_qs")]
+  pub(crate) fn check_query_keys(&self, input: &[u8], strict: bool) -> Result<(), Error> {
+    if self.max_fields.is_none() && self.max_depth.is_none() && !self.reject_duplicate_keys {
+      return Ok(());
+    }
+
+    let mut keys = HashSet::new();
+    let pairs = input.split(|b| *b == b'&').filter(|pair| !pair.is_empty());
+    for (fields, pair) in pairs.enumerate() {
+      if let Some(max) = self.max_fields.filter(|max| fields >= *max) {
+        return Err(ErrorCause::FieldCountLimit(max).into());
+      }
+
+      let raw = pair.split(|b| *b == b'=').next().unwrap_or_default();
+      let (key, _) = form_urlencoded::parse(raw).next().unwrap_or_default();
+      let brackets = if strict {
+        raw.iter().filter(|b| **b == b'[').count()
+      } else {
+        key.matches('[').count()
+      };
+      if let Some(max) = self.max_depth.filter(|max| brackets + 1 > *max) {
+        return Err(ErrorCause::DepthLimit(max).into());
+      }
+
+      let pushed = if strict {
+        raw.ends_with(b"[]")
+      } else {
+        key.ends_with("[]")
+      };
+      if self.reject_duplicate_keys && !pushed {
+        if let Some(key) = keys.replace(key.into_owned()) {
+          return Err(ErrorCause::DuplicateKey(key).into());
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// `serde_qs` config parsing keys no deeper than [`Limits::max_depth`], the top level struct taking one level
+  #[cfg(feature = "serde_qs")]
+  pub(crate) fn qs_config(&self, config: serde_qs::Config, strict: bool) -> serde_qs::Config {
+    self
+      .max_depth
+      .map_or(config, |depth| serde_qs::Config::new(depth.saturating_sub(1), strict))
+  }
+
+  /// Deserializes `T`, reporting exceeded limits with their own error and other failures through `map_err`
+  pub(crate) fn deserialize<'de, T, D, F>(&self, deserializer: D, map_err: F) -> Result<T, Error>
+  where
+    T: de::Deserialize<'de>,
+    D: Deserializer<'de>,
+    F: FnOnce(D::Error) -> Error,
+  {
+    if *self == Limits::NONE {
+      return T::deserialize(deserializer).map_err(map_err);
+    }
+
+    let state = State::new(*self);
+    T::deserialize(LimitedDeserializer {
+      inner: deserializer,
+      state: &state,
+    })
+    .map_err(|e| state.violation.take().map_or_else(|| map_err(e), Error::from))
+  }
+}
+
+/// Progress through the payload, shared by the wrappers of a single deserialization
+struct State {
+  limits: Limits,
+  depth: Cell<usize>,
+  fields: Cell<usize>,
+  capture_key: Cell<bool>,
+  key: Cell<Option<String>>,
+  violation: Cell<Option<ErrorCause>>,
+}
+
+impl State {
+  fn new(limits: Limits) -> Self {
+    State {
+      limits,
+      depth: Cell::new(0),
+      fields: Cell::new(0),
+      capture_key: Cell::new(false),
+      key: Cell::new(None),
+      violation: Cell::new(None),
+    }
+  }
+
+  /// Records the violation, handed back by [`Limits::deserialize`] in place of the deserializer error
+  fn fail<E: de::Error>(&self, error: ErrorCause) -> E {
+    let message = error.to_string();
+    self.violation.set(Some(error));
+    E::custom(message)
+  }
+
+  fn enter<E: de::Error>(&self) -> Result<(), E> {
+    let depth = self.depth.get() + 1;
+    match self.limits.max_depth {
+      Some(max) if depth > max => Err(self.fail(ErrorCause::DepthLimit(max))),
+      _ => {
+        self.depth.set(depth);
+        Ok(())
+      }
+    }
+  }
+
+  fn leave(&self) {
+    self.depth.set(self.depth.get().saturating_sub(1));
+  }
+
+  fn check_length<E: de::Error>(&self, length: usize) -> Result<(), E> {
+    match self.limits.max_collection_length {
+      Some(max) if length > max => Err(self.fail(ErrorCause::CollectionLengthLimit(max))),
+      _ => Ok(()),
+    }
+  }
+
+  fn count_field<E: de::Error>(&self) -> Result<(), E> {
+    let fields = self.fields.get() + 1;
+    self.fields.set(fields);
+    match self.limits.max_fields {
+      Some(max) if fields > max => Err(self.fail(ErrorCause::FieldCountLimit(max))),
+      _ => Ok(()),
+    }
+  }
+
+  fn check_string<E: de::Error>(&self, value: &str) -> Result<(), E> {
+    if let Some(max) = self.limits.max_string_length.filter(|max| value.len() > *max) {
+      return Err(self.fail(ErrorCause::StringLengthLimit(max)));
+    }
+    self.capture(|| value.to_owned());
+    Ok(())
+  }
+
+  /// Records the scalar just visited when it is a map key, for the duplicate key check
+  fn capture(&self, key: impl FnOnce() -> String) {
+    if self.capture_key.replace(false) {
+      self.key.set(Some(key()));
+    }
+  }
+}
+
+struct LimitedDeserializer<'s, D> {
+  inner: D,
+  state: &'s State,
+}
+
+macro_rules! forward_deserialize {
+  ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+    $(
+      fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, D::Error> {
+        self.inner.$method($($arg,)* LimitedVisitor { inner: visitor, state: self.state })
+      }
+    )*
+  };
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for LimitedDeserializer<'_, D> {
+  type Error = D::Error;
+
+  forward_deserialize! {
+    deserialize_any();
+    deserialize_bool();
+    deserialize_i8();
+    deserialize_i16();
+    deserialize_i32();
+    deserialize_i64();
+    deserialize_i128();
+    deserialize_u8();
+    deserialize_u16();
+    deserialize_u32();
+    deserialize_u64();
+    deserialize_u128();
+    deserialize_f32();
+    deserialize_f64();
+    deserialize_char();
+    deserialize_str();
+    deserialize_string();
+    deserialize_bytes();
+    deserialize_byte_buf();
+    deserialize_option();
+    deserialize_unit();
+    deserialize_unit_struct(name: &'static str);
+    deserialize_newtype_struct(name: &'static str);
+    deserialize_seq();
+    deserialize_tuple(len: usize);
+    deserialize_tuple_struct(name: &'static str, len: usize);
+    deserialize_map();
+    deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+    deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+    deserialize_identifier();
+  }
+
+  /// Skipped values are walked as well, formats such as `serde_json` skip them without visiting them otherwise
+  fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+    self.inner.deserialize_any(LimitedVisitor {
+      inner: visitor,
+      state: self.state,
+    })
+  }
+
+  fn is_human_readable(&self) -> bool {
+    self.inner.is_human_readable()
+  }
+}
+
+struct LimitedVisitor<'s, V> {
+  inner: V,
+  state: &'s State,
+}
+
+macro_rules! forward_visit {
+  ($($method:ident($ty:ty) => |$v:ident| $key:expr;)*) => {
+    $(
+      fn $method<E: de::Error>(self, v: $ty) -> Result<V::Value, E> {
+        self.state.capture(|| {
+          let $v = &v;
+          $key
+        });
+        self.inner.$method(v)
+      }
+    )*
+  };
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for LimitedVisitor<'_, V> {
+  type Value = V::Value;
+
+  fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    self.inner.expecting(formatter)
+  }
+
+  forward_visit! {
+    visit_bool(bool) => |v| v.to_string();
+    visit_i8(i8) => |v| v.to_string();
+    visit_i16(i16) => |v| v.to_string();
+    visit_i32(i32) => |v| v.to_string();
+    visit_i64(i64) => |v| v.to_string();
+    visit_i128(i128) => |v| v.to_string();
+    visit_u8(u8) => |v| v.to_string();
+    visit_u16(u16) => |v| v.to_string();
+    visit_u32(u32) => |v| v.to_string();
+    visit_u64(u64) => |v| v.to_string();
+    visit_u128(u128) => |v| v.to_string();
+    visit_f32(f32) => |v| v.to_string();
+    visit_f64(f64) => |v| v.to_string();
+    visit_char(char) => |v| v.to_string();
+    visit_bytes(&[u8]) => |v| String::from_utf8_lossy(v).into_owned();
+    visit_borrowed_bytes(&'de [u8]) => |v| String::from_utf8_lossy(v).into_owned();
+    visit_byte_buf(Vec<u8>) => |v| String::from_utf8_lossy(v).into_owned();
+  }
+
+  fn visit_str<E: de::Error>(self, v: &str) -> Result<V::Value, E> {
+    self.state.check_string(v)?;
+    self.inner.visit_str(v)
+  }
+
+  fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<V::Value, E> {
+    self.state.check_string(v)?;
+    self.inner.visit_borrowed_str(v)
+  }
+
+  fn visit_string<E: de::Error>(self, v: String) -> Result<V::Value, E> {
+    self.state.check_string(&v)?;
+    self.inner.visit_string(v)
+  }
+
+  fn visit_none<E: de::Error>(self) -> Result<V::Value, E> {
+    self.inner.visit_none()
+  }
+
+  fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<V::Value, D::Error> {
+    self.inner.visit_some(LimitedDeserializer {
+      inner: deserializer,
+      state: self.state,
+    })
+  }
+
+  fn visit_unit<E: de::Error>(self) -> Result<V::Value, E> {
+    self.inner.visit_unit()
+  }
+
+  fn visit_newtype_struct<D: Deserializer<'de>>(self, deserializer: D) -> Result<V::Value, D::Error> {
+    self.inner.visit_newtype_struct(LimitedDeserializer {
+      inner: deserializer,
+      state: self.state,
+    })
+  }
+
+  fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<V::Value, A::Error> {
+    self.state.capture_key.set(false);
+    self.state.enter()?;
+    let value = self.inner.visit_seq(LimitedSeq {
+      inner: seq,
+      state: self.state,
+      length: 0,
+    });
+    self.state.leave();
+    value
+  }
+
+  fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+    self.state.capture_key.set(false);
+    self.state.enter()?;
+    let value = self.inner.visit_map(LimitedMap {
+      inner: map,
+      state: self.state,
+      length: 0,
+      keys: HashSet::new(),
+    });
+    self.state.leave();
+    value
+  }
+
+  fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<V::Value, A::Error> {
+    self.state.capture_key.set(false);
+    self.inner.visit_enum(LimitedEnum {
+      inner: data,
+      state: self.state,
+    })
+  }
+}
+
+struct LimitedSeed<'s, S> {
+  inner: S,
+  state: &'s State,
+}
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for LimitedSeed<'_, S> {
+  type Value = S::Value;
+
+  fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<S::Value, D::Error> {
+    self.inner.deserialize(LimitedDeserializer {
+      inner: deserializer,
+      state: self.state,
+    })
+  }
+}
+
+struct LimitedSeq<'s, A> {
+  inner: A,
+  state: &'s State,
+  length: usize,
+}
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for LimitedSeq<'_, A> {
+  type Error = A::Error;
+
+  fn next_element_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<Option<S::Value>, A::Error> {
+    let element = self.inner.next_element_seed(LimitedSeed {
+      inner: seed,
+      state: self.state,
+    })?;
+    if element.is_some() {
+      self.length += 1;
+      self.state.check_length(self.length)?;
+    }
+    Ok(element)
+  }
+
+  fn size_hint(&self) -> Option<usize> {
+    self.inner.size_hint()
+  }
+}
+
+struct LimitedMap<'s, A> {
+  inner: A,
+  state: &'s State,
+  length: usize,
+  keys: HashSet<String>,
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for LimitedMap<'_, A> {
+  type Error = A::Error;
+
+  fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, A::Error> {
+    self.state.capture_key.set(self.state.limits.reject_duplicate_keys);
+    let key = self.inner.next_key_seed(LimitedSeed {
+      inner: seed,
+      state: self.state,
+    });
+    self.state.capture_key.set(false);
+    let name = self.state.key.take();
+
+    let key = key?;
+    if key.is_some() {
+      self.length += 1;
+      self.state.check_length(self.length)?;
+      self.state.count_field()?;
+    }
+    if let Some(name) = name.and_then(|name| self.keys.replace(name)) {
+      return Err(self.state.fail(ErrorCause::DuplicateKey(name)));
+    }
+    Ok(key)
+  }
+
+  fn next_value_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<S::Value, A::Error> {
+    self.inner.next_value_seed(LimitedSeed {
+      inner: seed,
+      state: self.state,
+    })
+  }
+
+  fn size_hint(&self) -> Option<usize> {
+    self.inner.size_hint()
+  }
+}
+
+struct LimitedEnum<'s, A> {
+  inner: A,
+  state: &'s State,
+}
+
+impl<'de, 's, A: EnumAccess<'de>> EnumAccess<'de> for LimitedEnum<'s, A> {
+  type Error = A::Error;
+  type Variant = LimitedVariant<'s, A::Variant>;
+
+  fn variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<(S::Value, Self::Variant), A::Error> {
+    let (value, variant) = self.inner.variant_seed(LimitedSeed {
+      inner: seed,
+      state: self.state,
+    })?;
+
+    Ok((
+      value,
+      LimitedVariant {
+        inner: variant,
+        state: self.state,
+      },
+    ))
+  }
+}
+
+struct LimitedVariant<'s, A> {
+  inner: A,
+  state: &'s State,
+}
+
+impl<'de, A: VariantAccess<'de>> VariantAccess<'de> for LimitedVariant<'_, A> {
+  type Error = A::Error;
+
+  fn unit_variant(self) -> Result<(), A::Error> {
+    self.inner.unit_variant()
+  }
+
+  fn newtype_variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<S::Value, A::Error> {
+    self.inner.newtype_variant_seed(LimitedSeed {
+      inner: seed,
+      state: self.state,
+    })
+  }
+
+  fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, A::Error> {
+    self.inner.tuple_variant(
+      len,
+      LimitedVisitor {
+        inner: visitor,
+        state: self.state,
+      },
+    )
+  }
+
+  fn struct_variant<V: Visitor<'de>>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, A::Error> {
+    self.inner.struct_variant(
+      fields,
+      LimitedVisitor {
+        inner: visitor,
+        state: self.state,
+      },
+    )
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::error::{Error, ErrorCause};
+  use crate::limits::Limits;
+  use actix_web::error::JsonPayloadError;
+  use serde::Deserialize;
+  use std::collections::HashMap;
+
+  #[derive(Debug, Deserialize)]
+  #[allow(dead_code)]
+  struct Data {
+    name: String,
+    tags: Vec<String>,
+    #[serde(default)]
+    extra: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    ids: HashMap<u32, u8>,
+  }
+
+  fn deserialize(limits: Limits, json: &str) -> Result<Data, ErrorCause> {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    limits
+      .deserialize(&mut deserializer, |e| JsonPayloadError::Deserialize(e).into())
+      .map_err(Error::into_cause)
+  }
+
+  #[test]
+  fn test_limits() {
+    let json = r#"{"name": "netwo", "tags": ["a", "b"], "extra": {"x": {"y": [1, 2, 3]}}}"#;
+    assert!(deserialize(Limits::default(), json).is_ok());
+
+    let limits = Limits::default()
+      .max_depth(4)
+      .max_collection_length(3)
+      .max_string_length(5)
+      .max_fields(5)
+      .reject_duplicate_keys(true);
+    assert!(deserialize(limits, json).is_ok());
+
+    let res = deserialize(limits.max_depth(3), json);
+    assert!(matches!(res, Err(ErrorCause::DepthLimit(3))));
+
+    let res = deserialize(limits.max_collection_length(2), json);
+    assert!(matches!(res, Err(ErrorCause::CollectionLengthLimit(2))));
+
+    let res = deserialize(limits.max_string_length(4), json);
+    assert!(matches!(res, Err(ErrorCause::StringLengthLimit(4))));
+
+    let res = deserialize(limits.max_fields(4), json);
+    assert!(matches!(res, Err(ErrorCause::FieldCountLimit(4))));
+
+    let res = deserialize(limits, r#"{"name": "netwo", "tags": [], "extra": {"x": 1, "x": 2}}"#);
+    assert!(matches!(res, Err(ErrorCause::DuplicateKey(key)) if key == "x"));
+    assert!(
+      deserialize(
+        Limits::default(),
+        r#"{"name": "netwo", "tags": [], "extra": {"x": 1, "x": 2}}"#
+      )
+      .is_ok()
+    );
+
+    let res = deserialize(limits, r#"{"name": "netwo", "tags": [], "ids": {"1": 1, "1": 2}}"#);
+    assert!(matches!(res, Err(ErrorCause::DuplicateKey(key)) if key == "1"));
+    assert!(deserialize(limits, r#"{"name": "netwo", "tags": [], "ids": {"1": 1, "2": 2}}"#).is_ok());
+
+    let res = deserialize(limits, r#"{"name": 1, "tags": []}"#);
+    assert!(matches!(
+      res,
+      Err(ErrorCause::JsonPayloadError(JsonPayloadError::Deserialize(_)))
+    ));
+  }
+}
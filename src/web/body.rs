@@ -0,0 +1,279 @@
+use crate::context::ContextFallback;
+use crate::error::{ErrorCause, Source};
+use crate::web::config::{ErrorHandler, handle_error};
+use crate::web::json::accepts_json;
+use crate::web::{Form, Json, JsonConfig, ValidationErrorHandler};
+use actix_web::dev::Payload;
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest, web};
+use derive_more::{AsRef, Deref, DerefMut, Display, From};
+use futures::future::{LocalBoxFuture, err};
+use futures::{FutureExt, TryFutureExt};
+use garde::Validate;
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+
+/// Body extractor picking the deserializer from the `Content-Type` header
+///
+/// JSON bodies (`application/json`, `+json` types and the ones accepted by [`JsonConfig::content_type`]) are read as
+/// [`Json`], `application/x-www-form-urlencoded` ones as [`Form`], or as [`QsForm`](crate::web::QsForm) when
+/// [`BodyConfig::qs_forms`] is enabled.
+/// The payload is deserialized and validated once, following the config of the matching extractor.
+/// Other content types are rejected with a 415.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Deref, DerefMut, AsRef, Display, From)]
+pub struct Body<T>(pub T);
+
+impl<T> Body<T> {
+  pub fn into_inner(self) -> T {
+    self.0
+  }
+}
+
+impl_serialize!(Body);
+
+impl<T> FromRequest for Body<T>
+where
+  T: DeserializeOwned + Validate + 'static,
+  T::Context: ContextFallback,
+{
+  type Error = Error;
+  type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+  #[inline]
+  fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+    let mime = req.mime_type().ok().flatten();
+
+    match mime {
+      Some(mime) if accepts_json(JsonConfig::from_req(req), mime.clone()) => Json::<T>::from_request(req, payload)
+        .map_ok(|json| Body(json.0))
+        .boxed_local(),
+      Some(mime) if mime.essence_str() == mime::APPLICATION_WWW_FORM_URLENCODED.essence_str() => {
+        #[cfg(feature = "serde_qs")]
+        if BodyConfig::from_req(req).qs_forms {
+          return crate::web::QsForm::<T>::from_request(req, payload)
+            .map_ok(|form| Body(form.into_inner()))
+            .boxed_local();
+        }
+
+        Form::<T>::from_request(req, payload)
+          .map_ok(|form| Body(form.0))
+          .boxed_local()
+      }
+      _ => {
+        log::debug!(
+          "Unsupported content type for Body extractor. \
+                         Request path: {}",
+          req.path()
+        );
+
+        let err_handler = BodyConfig::from_req(req).err_handler.as_deref().map(|h| h as _);
+        err(handle_error::<T>(
+          ErrorCause::UnsupportedContentType.into(),
+          req,
+          Source::Body,
+          err_handler,
+        ))
+        .boxed_local()
+      }
+    }
+  }
+}
+
+/// Configuration of the [`Body`] extractor
+///
+/// Limits and error handlers of each format are taken from [`JsonConfig`](crate::web::JsonConfig) and
+/// [`FormConfig`](crate::web::FormConfig), the error handler set here only receives unsupported content types.
+#[derive(Clone)]
+pub struct BodyConfig {
+  err_handler: Option<ErrorHandler>,
+  #[cfg(feature = "serde_qs")]
+  qs_forms: bool,
+}
+
+impl BodyConfig {
+  pub fn error_handler<F>(mut self, f: F) -> Self
+  where
+    F: Fn(crate::error::Error, &HttpRequest) -> Error + Send + Sync + 'static,
+  {
+    self.err_handler = Some(Arc::new(f));
+    self
+  }
+
+  /// Error handler receiving the extractor metadata, see [`ValidationErrorHandler`]
+  pub fn handler<H>(mut self, handler: H) -> Self
+  where
+    H: ValidationErrorHandler + Send + Sync + 'static,
+  {
+    self.err_handler = Some(Arc::new(handler));
+    self
+  }
+
+  /// Reads urlencoded bodies with `serde_qs`, supporting nested forms
+  #[cfg(feature = "serde_qs")]
+  pub fn qs_forms(mut self, qs_forms: bool) -> Self {
+    self.qs_forms = qs_forms;
+    self
+  }
+
+  pub fn from_req(req: &HttpRequest) -> &Self {
+    req
+      .app_data::<Self>()
+      .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
+      .unwrap_or(&DEFAULT_CONFIG)
+  }
+}
+
+const DEFAULT_CONFIG: BodyConfig = BodyConfig {
+  err_handler: None,
+  #[cfg(feature = "serde_qs")]
+  qs_forms: false,
+};
+
+impl Default for BodyConfig {
+  fn default() -> Self {
+    DEFAULT_CONFIG
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::web::{Body, BodyConfig, JsonConfig};
+  use actix_http::StatusCode;
+  use actix_http::header::CONTENT_TYPE;
+  use actix_web::error::InternalError;
+  use actix_web::test::{TestRequest, call_service, init_service, read_body};
+  use actix_web::web::{post, resource};
+  use actix_web::{App, HttpResponse};
+  use garde::Validate;
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Debug, PartialEq, Validate, Serialize, Deserialize)]
+  struct BodyData {
+    #[garde(range(min = 18, max = 28))]
+    age: u8,
+  }
+
+  async fn test_handler(body: Body<BodyData>) -> HttpResponse {
+    HttpResponse::Ok().body(body.age.to_string())
+  }
+
+  #[tokio::test]
+  async fn test_body_dispatch() {
+    let app = init_service(App::new().service(resource("/").route(post().to(test_handler)))).await;
+
+    let req = TestRequest::post().uri("/").set_json(BodyData { age: 24 }).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = TestRequest::post().uri("/").set_form(BodyData { age: 24 }).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = TestRequest::post().uri("/").set_json(BodyData { age: 30 }).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = TestRequest::post().uri("/").set_form(BodyData { age: 30 }).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "application/problem+json"))
+      .set_payload("{\"age\": 24}")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "text/plain"))
+      .set_payload("24")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert_eq!(read_body(resp).await, "Unsupported content type");
+
+    let req = TestRequest::post().uri("/").set_payload("{\"age\": 24}").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+  }
+
+  #[tokio::test]
+  async fn test_body_custom_config() {
+    let app = init_service(
+      App::new()
+        .app_data(
+          BodyConfig::default()
+            .error_handler(|err, _req| InternalError::from_response(err, HttpResponse::Conflict().finish()).into()),
+        )
+        .app_data(
+          JsonConfig::default()
+            .content_type(|mime| mime.essence_str() == "application/x-age")
+            .error_handler(|err, _req| InternalError::from_response(err, HttpResponse::ImATeapot().finish()).into()),
+        )
+        .service(resource("/").route(post().to(test_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "application/x-age"))
+      .set_payload("{\"age\": 24}")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = TestRequest::post().uri("/").set_json(BodyData { age: 30 }).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::IM_A_TEAPOT);
+
+    let req = TestRequest::post().uri("/").set_form(BodyData { age: 30 }).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "text/plain"))
+      .set_payload("24")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+  }
+
+  #[cfg(feature = "serde_qs")]
+  #[tokio::test]
+  async fn test_body_qs_forms() {
+    #[derive(Debug, Validate, Deserialize)]
+    struct Nested {
+      #[garde(dive)]
+      data: BodyData,
+    }
+
+    async fn nested_handler(body: Body<Nested>) -> HttpResponse {
+      HttpResponse::Ok().body(body.data.age.to_string())
+    }
+
+    let app = init_service(
+      App::new()
+        .app_data(BodyConfig::default().qs_forms(true))
+        .service(resource("/").route(post().to(nested_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+      .set_payload("data[age]=24")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+      .set_payload("data[age]=30")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+  }
+}
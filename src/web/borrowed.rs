@@ -0,0 +1,392 @@
+use crate::context::ContextFallback;
+use crate::context_for_request;
+use crate::error::Source;
+use crate::web::config::handle_error;
+use crate::web::{PathConfig, QueryConfig};
+use actix_router::PathDeserializer;
+use actix_web::dev::Payload;
+use actix_web::error::{PathError, QueryPayloadError};
+use actix_web::{Error, FromRequest, HttpRequest};
+use futures::future::{Ready, err, ok};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::rc::Rc;
+use yoke::{Yoke, Yokeable};
+
+/// Types deserialized borrowing from the request, named by their `'static` version
+///
+/// Implemented for every type deriving [`Yokeable`], which requires the `yoke` crate as a dependency. `User<'a>` is the
+/// type handed to the handler by [`PathRef<User<'static>>`](PathRef) and [`QueryRef`], validated with the context of
+/// `User<'static>`.
+///
+/// ```rust
+/// use actix_web::HttpResponse;
+/// use garde::Validate;
+/// use garde_actix_web::web::PathRef;
+/// use serde::Deserialize;
+/// use std::borrow::Cow;
+/// use yoke::Yokeable;
+///
+/// #[derive(Deserialize, Validate, Yokeable)]
+/// struct User<'a> {
+///   #[garde(ascii, length(min = 3, max = 25))]
+///   username: &'a str,
+///   // falls back to an owned string when the value has to be decoded
+///   #[garde(length(max = 25))]
+///   #[serde(borrow)]
+///   display_name: Cow<'a, str>,
+/// }
+///
+/// async fn handler(user: PathRef<User<'static>>) -> HttpResponse {
+///   HttpResponse::Ok().body(user.get().username.to_string())
+/// }
+/// ```
+pub trait Borrowed:
+  for<'a> Yokeable<'a, Output: Deserialize<'a> + Validate<Context = <Self as Validate>::Context>> + Validate + 'static
+{
+}
+
+impl<T> Borrowed for T where
+  T: for<'a> Yokeable<'a, Output: Deserialize<'a> + Validate<Context = <T as Validate>::Context>> + Validate + 'static
+{
+}
+
+/// Path extractor deserializing and validating a [`Borrowed`] type without copying the path segments
+///
+/// The data is deserialized and validated once, borrowing from the request kept by the extractor.
+/// Errors go through the [`PathConfig`] error handler and status policy.
+pub struct PathRef<T: Borrowed> {
+  data: Yoke<T, Option<Rc<HttpRequest>>>,
+}
+
+impl<T: Borrowed> PathRef<T> {
+  /// Borrowed data, validated during extraction
+  pub fn get(&self) -> &<T as Yokeable<'_>>::Output {
+    self.data.get()
+  }
+
+  /// Converts the data into an owned type, for values outliving the request
+  pub fn into_owned<O>(self) -> O
+  where
+    O: for<'a> From<&'a <T as Yokeable<'a>>::Output>,
+  {
+    O::from(self.data.get())
+  }
+}
+
+impl<T: Borrowed> fmt::Debug for PathRef<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("PathRef")
+      .field("path", &self.data.backing_cart().as_ref().map(|req| req.match_info()))
+      .finish()
+  }
+}
+
+impl<T: Borrowed> Clone for PathRef<T>
+where
+  for<'a> <T as Yokeable<'a>>::Output: Clone,
+{
+  fn clone(&self) -> Self {
+    PathRef {
+      data: self.data.clone(),
+    }
+  }
+}
+
+/// Wraps owned data, borrowing from no request
+impl<T: Borrowed> From<T> for PathRef<T> {
+  fn from(data: T) -> Self {
+    PathRef {
+      data: Yoke::new_owned(data),
+    }
+  }
+}
+
+impl<T: Borrowed> fmt::Display for PathRef<T>
+where
+  for<'a> <T as Yokeable<'a>>::Output: fmt::Display,
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt::Display::fmt(self.get(), f)
+  }
+}
+
+impl<T: Borrowed> Serialize for PathRef<T>
+where
+  for<'a> <T as Yokeable<'a>>::Output: Serialize,
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: serde::Serializer,
+  {
+    self.get().serialize(serializer)
+  }
+}
+
+impl<T> FromRequest for PathRef<T>
+where
+  T: Borrowed,
+  T::Context: ContextFallback,
+{
+  type Error = Error;
+  type Future = Ready<Result<Self, Error>>;
+
+  #[inline]
+  fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+    let data = Yoke::try_attach_to_cart(Rc::new(req.clone()), |req| {
+      let data = deserialize_path::<T>(req)?;
+      validate_borrowed::<T>(&data, req, Source::Path).map(|()| data)
+    });
+
+    match data {
+      Ok(data) => ok(PathRef {
+        data: data.wrap_cart_in_option(),
+      }),
+      Err(e) => {
+        log::debug!(
+          "Failed during PathRef extractor deserialization. \
+                         Request path: {:?}",
+          req.path()
+        );
+
+        let err_handler = PathConfig::from_req(req).err_handler.as_deref().map(|h| h as _);
+        err(handle_error::<T>(e, req, Source::Path, err_handler))
+      }
+    }
+  }
+}
+
+/// Query extractor deserializing and validating a [`Borrowed`] type without copying the query string
+///
+/// The data is deserialized and validated once, borrowing from the request kept by the extractor.
+/// Errors go through the [`QueryConfig`] error handler, its limits apply.
+pub struct QueryRef<T: Borrowed> {
+  data: Yoke<T, Option<Rc<HttpRequest>>>,
+}
+
+impl<T: Borrowed> QueryRef<T> {
+  /// Borrowed data, validated during extraction
+  pub fn get(&self) -> &<T as Yokeable<'_>>::Output {
+    self.data.get()
+  }
+
+  /// Converts the data into an owned type, for values outliving the request
+  pub fn into_owned<O>(self) -> O
+  where
+    O: for<'a> From<&'a <T as Yokeable<'a>>::Output>,
+  {
+    O::from(self.data.get())
+  }
+}
+
+impl<T: Borrowed> fmt::Debug for QueryRef<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("QueryRef")
+      .field(
+        "query",
+        &self.data.backing_cart().as_ref().map(|req| req.query_string()),
+      )
+      .finish()
+  }
+}
+
+impl<T: Borrowed> Clone for QueryRef<T>
+where
+  for<'a> <T as Yokeable<'a>>::Output: Clone,
+{
+  fn clone(&self) -> Self {
+    QueryRef {
+      data: self.data.clone(),
+    }
+  }
+}
+
+/// Wraps owned data, borrowing from no request
+impl<T: Borrowed> From<T> for QueryRef<T> {
+  fn from(data: T) -> Self {
+    QueryRef {
+      data: Yoke::new_owned(data),
+    }
+  }
+}
+
+impl<T: Borrowed> fmt::Display for QueryRef<T>
+where
+  for<'a> <T as Yokeable<'a>>::Output: fmt::Display,
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt::Display::fmt(self.get(), f)
+  }
+}
+
+impl<T: Borrowed> Serialize for QueryRef<T>
+where
+  for<'a> <T as Yokeable<'a>>::Output: Serialize,
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: serde::Serializer,
+  {
+    self.get().serialize(serializer)
+  }
+}
+
+impl<T> FromRequest for QueryRef<T>
+where
+  T: Borrowed,
+  T::Context: ContextFallback,
+{
+  type Error = Error;
+  type Future = Ready<Result<Self, Error>>;
+
+  #[inline]
+  fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+    let data = Yoke::try_attach_to_cart(Rc::new(req.clone()), |req| {
+      let data = deserialize_query::<T>(req)?;
+      validate_borrowed::<T>(&data, req, Source::Query).map(|()| data)
+    });
+
+    match data {
+      Ok(data) => ok(QueryRef {
+        data: data.wrap_cart_in_option(),
+      }),
+      Err(e) => {
+        log::debug!(
+          "Failed during QueryRef extractor deserialization. \
+                     Request path: {:?}",
+          req.path()
+        );
+
+        let err_handler = req
+          .app_data::<QueryConfig>()
+          .and_then(|c| c.err_handler.as_deref())
+          .map(|h| h as _);
+        err(handle_error::<T>(e, req, Source::Query, err_handler))
+      }
+    }
+  }
+}
+
+fn deserialize_path<T: Borrowed>(req: &HttpRequest) -> Result<<T as Yokeable<'_>>::Output, crate::error::Error> {
+  <T as Yokeable<'_>>::Output::deserialize(PathDeserializer::new(req.match_info()))
+    .map_err(|e| crate::error::Error::new(PathError::Deserialize(e), Source::Path))
+}
+
+fn deserialize_query<T: Borrowed>(req: &HttpRequest) -> Result<<T as Yokeable<'_>>::Output, crate::error::Error> {
+  let limits = req.app_data::<QueryConfig>().map(|c| c.limits).unwrap_or_default();
+  let deserializer = serde_urlencoded::Deserializer::new(form_urlencoded::parse(req.query_string().as_bytes()));
+
+  limits
+    .deserialize(deserializer, |e| QueryPayloadError::Deserialize(e).into())
+    .map_err(|e| e.with_origin(Source::Query))
+}
+
+pub(crate) fn validate_borrowed<T>(
+  data: &<T as Yokeable<'_>>::Output,
+  req: &HttpRequest,
+  source: Source,
+) -> Result<(), crate::error::Error>
+where
+  T: Borrowed,
+  T::Context: ContextFallback,
+{
+  let context = context_for_request::<T>(req, source)?;
+
+  data
+    .validate_with(&context)
+    .map_err(|report| crate::error::Error::new(report, source))
+}
+
+#[cfg(test)]
+mod test {
+  use crate::web::{PathRef, QueryRef};
+  use actix_http::StatusCode;
+  use actix_web::test::{TestRequest, call_service, init_service, read_body};
+  use actix_web::web::{get, resource};
+  use actix_web::{App, HttpResponse};
+  use garde::Validate;
+  use serde::Deserialize;
+  use std::borrow::Cow;
+  use yoke::Yokeable;
+
+  #[derive(Debug, Validate, Deserialize, Yokeable)]
+  struct UserData<'a> {
+    #[garde(ascii, length(min = 3, max = 10))]
+    name: &'a str,
+    #[garde(length(max = 10))]
+    #[serde(borrow)]
+    city: Cow<'a, str>,
+  }
+
+  struct OwnedUserData {
+    city: String,
+  }
+
+  impl From<&UserData<'_>> for OwnedUserData {
+    fn from(user: &UserData<'_>) -> Self {
+      OwnedUserData {
+        city: user.city.to_string(),
+      }
+    }
+  }
+
+  async fn test_path_handler(user: PathRef<UserData<'static>>) -> HttpResponse {
+    let data = user.get();
+    HttpResponse::Ok().body(format!(
+      "{} {} {}",
+      data.name,
+      data.city,
+      matches!(data.city, Cow::Borrowed(_))
+    ))
+  }
+
+  async fn test_query_handler(user: QueryRef<UserData<'static>>) -> HttpResponse {
+    let data = user.get();
+    let (name, borrowed) = (data.name.to_string(), matches!(data.city, Cow::Borrowed(_)));
+    let owned: OwnedUserData = user.into_owned();
+    HttpResponse::Ok().body(format!("{} {} {}", name, owned.city, borrowed))
+  }
+
+  #[tokio::test]
+  async fn test_path_ref() {
+    let app = init_service(App::new().service(resource("/{name}/{city}").route(get().to(test_path_handler)))).await;
+
+    let req = TestRequest::get().uri("/netwo/paris").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(read_body(resp).await, "netwo paris true");
+
+    let req = TestRequest::get().uri("/netwo/aix%20en").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(read_body(resp).await, "netwo aix en true");
+
+    let req = TestRequest::get().uri("/ab/paris").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+  }
+
+  #[tokio::test]
+  async fn test_query_ref() {
+    let app = init_service(App::new().service(resource("/").route(get().to(test_query_handler)))).await;
+
+    let req = TestRequest::get().uri("/?name=netwo&city=paris").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(read_body(resp).await, "netwo paris true");
+
+    let req = TestRequest::get().uri("/?name=netwo&city=aix+en").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(read_body(resp).await, "netwo aix en false");
+
+    let req = TestRequest::get().uri("/?name=ab&city=paris").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = TestRequest::get().uri("/?name=netwo").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+  }
+}
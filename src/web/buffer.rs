@@ -0,0 +1,118 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use actix_http::ContentEncoding;
+use actix_http::error::PayloadError;
+use actix_http::header::CONTENT_LENGTH;
+use actix_web::HttpRequest;
+use actix_web::dev::{Decompress, Payload};
+use actix_web::error::{JsonPayloadError, UrlencodedError};
+use actix_web::web::BytesMut;
+use futures::{StreamExt, ready};
+
+use crate::error::{ErrorCause, Source};
+
+/// Body of a request buffered up to a limit, decompressed and checked as `JsonBody` and `UrlEncoded` do
+///
+/// Errors are the ones of the extractor: `JsonPayloadError` for [`Source::Json`], `PayloadError` for
+/// [`Source::Either`], `UrlencodedError` for the forms.
+pub(crate) struct BufferedBody {
+  stream: Decompress<Payload>,
+  body: BytesMut,
+  limit: usize,
+  source: Source,
+  err: Option<ErrorCause>,
+}
+
+impl BufferedBody {
+  /// Buffers the payload if the content type of the request is `accepted` and its length within `limit`
+  pub(crate) fn new(req: &HttpRequest, payload: &mut Payload, source: Source, accepted: bool, limit: usize) -> Self {
+    let stream = Decompress::from_headers(payload.take(), req.headers());
+    BufferedBody::with_stream(req, stream, source, accepted, limit)
+  }
+
+  /// Buffers the payload without decompressing it, for extractors reading the buffered body again
+  pub(crate) fn encoded(req: &HttpRequest, payload: &mut Payload, source: Source, limit: usize) -> Self {
+    let stream = Decompress::new(payload.take(), ContentEncoding::Identity);
+    BufferedBody::with_stream(req, stream, source, true, limit)
+  }
+
+  fn with_stream(req: &HttpRequest, stream: Decompress<Payload>, source: Source, accepted: bool, limit: usize) -> Self {
+    let length = req
+      .headers()
+      .get(CONTENT_LENGTH)
+      .and_then(|l| l.to_str().ok())
+      .and_then(|l| l.parse::<usize>().ok());
+
+    let err = if accepted {
+      length
+        .filter(|length| *length > limit)
+        .map(|length| overflow_known_length(source, length, limit))
+    } else {
+      Some(content_type(source))
+    };
+
+    BufferedBody {
+      stream,
+      body: BytesMut::with_capacity(length.unwrap_or_default().min(limit)),
+      limit,
+      source,
+      err,
+    }
+  }
+}
+
+impl Future for BufferedBody {
+  type Output = Result<BytesMut, crate::error::Error>;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let this = self.get_mut();
+
+    if let Some(err) = this.err.take() {
+      return Poll::Ready(Err(err.into()));
+    }
+
+    while let Some(chunk) = ready!(this.stream.poll_next_unpin(cx)) {
+      let chunk = chunk.map_err(|e| payload(this.source, e))?;
+      let size = this.body.len() + chunk.len();
+      if size > this.limit {
+        return Poll::Ready(Err(overflow(this.source, size, this.limit).into()));
+      }
+      this.body.extend_from_slice(&chunk);
+    }
+
+    Poll::Ready(Ok(std::mem::take(&mut this.body)))
+  }
+}
+
+fn content_type(source: Source) -> ErrorCause {
+  match source {
+    Source::Json => JsonPayloadError::ContentType.into(),
+    _ => UrlencodedError::ContentType.into(),
+  }
+}
+
+fn overflow_known_length(source: Source, length: usize, limit: usize) -> ErrorCause {
+  match source {
+    Source::Json => JsonPayloadError::OverflowKnownLength { length, limit }.into(),
+    Source::Either => PayloadError::Overflow.into(),
+    _ => UrlencodedError::Overflow { size: length, limit }.into(),
+  }
+}
+
+fn overflow(source: Source, size: usize, limit: usize) -> ErrorCause {
+  match source {
+    Source::Json => JsonPayloadError::Overflow { limit }.into(),
+    Source::Either => PayloadError::Overflow.into(),
+    _ => UrlencodedError::Overflow { size, limit }.into(),
+  }
+}
+
+fn payload(source: Source, err: PayloadError) -> ErrorCause {
+  match source {
+    Source::Json => JsonPayloadError::Payload(err).into(),
+    Source::Either => err.into(),
+    _ => UrlencodedError::Payload(err).into(),
+  }
+}
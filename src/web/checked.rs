@@ -0,0 +1,239 @@
+use crate::async_validate::check_async_for_request;
+use crate::check_for_request;
+use crate::context::ContextFallback;
+use crate::error::Location;
+use crate::web::GardeExtractor;
+use crate::web::config::handle_error;
+use actix_web::dev::Payload;
+use actix_web::{Error, FromRequest, HttpRequest};
+use futures::FutureExt;
+use futures::future::LocalBoxFuture;
+use garde::{Report, Validate};
+use serde::Serialize;
+use std::{fmt, ops};
+
+/// Extractor handing the validation report to the handler instead of rejecting the request
+///
+/// Deserialization failures still follow the extractor's error path, e.g. to re-render an HTML form with its errors:
+///
+/// ```rust
+/// use actix_web::HttpResponse;
+/// use garde::Validate;
+/// use garde_actix_web::web::{Checked, Form};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Validate)]
+/// struct SignUp {
+///   #[garde(length(min = 3))]
+///   username: String,
+/// }
+///
+/// async fn sign_up(form: Checked<Form<SignUp>>) -> HttpResponse {
+///   match form.report() {
+///     Some(report) => HttpResponse::UnprocessableEntity().body(format!("{}: {}", form.username, report)),
+///     None => HttpResponse::Ok().finish(),
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Checked<E> {
+  value: E,
+  report: Option<Report>,
+}
+
+impl<E> Checked<E> {
+  pub fn into_inner(self) -> E {
+    self.value
+  }
+
+  /// Validation errors, `None` when the data is valid
+  pub fn report(&self) -> Option<&Report> {
+    self.report.as_ref()
+  }
+
+  pub fn is_valid(&self) -> bool {
+    self.report.is_none()
+  }
+
+  pub fn into_parts(self) -> (E, Option<Report>) {
+    (self.value, self.report)
+  }
+}
+
+impl<E> ops::Deref for Checked<E> {
+  type Target = E;
+
+  fn deref(&self) -> &E {
+    &self.value
+  }
+}
+
+impl<E: fmt::Display> fmt::Display for Checked<E> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt::Display::fmt(&self.value, f)
+  }
+}
+
+/// Wraps a value without validation report
+impl<E> From<E> for Checked<E> {
+  fn from(value: E) -> Self {
+    Checked { value, report: None }
+  }
+}
+
+impl<E: Serialize> Serialize for Checked<E> {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: serde::Serializer,
+  {
+    self.value.serialize(serializer)
+  }
+}
+
+impl<E> FromRequest for Checked<E>
+where
+  E: GardeExtractor + 'static,
+  <E::Inner as Validate>::Context: ContextFallback,
+{
+  type Error = Error;
+  type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+  #[inline]
+  fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+    let req = req.clone();
+    let inner = E::extract_inner(&req, payload);
+
+    async move {
+      let res = match inner.await {
+        Ok(data) => check_for_request(&data, &req, E::SOURCE).map(|report| (data, report)),
+        Err(e) => Err(e.with_origin(E::SOURCE)),
+      };
+
+      match res {
+        Ok((data, None)) if E::SOURCE.location() == Location::Body => {
+          let report = check_async_for_request(&data, &req).await;
+          Ok(Checked {
+            value: E::from_inner(data),
+            report,
+          })
+        }
+        Ok((data, report)) => Ok(Checked {
+          value: E::from_inner(data),
+          report,
+        }),
+        Err(e) => {
+          log::debug!(
+            "Failed during {} extractor deserialization. \
+                         Request path: {}",
+            E::SOURCE,
+            req.path()
+          );
+
+          Err(handle_error::<E::Inner>(e, &req, E::SOURCE, E::err_handler(&req)))
+        }
+      }
+    }
+    .boxed_local()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::web::{Checked, Form, Json, Query};
+  use actix_http::StatusCode;
+  use actix_http::header::CONTENT_TYPE;
+  use actix_web::test::{TestRequest, call_service, init_service, read_body};
+  use actix_web::web::{get, post, resource};
+  use actix_web::{App, HttpResponse};
+  use garde::Validate;
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Debug, PartialEq, Validate, Serialize, Deserialize)]
+  struct FormData {
+    #[garde(range(min = 18, max = 28))]
+    age: u8,
+  }
+
+  async fn test_form_handler(form: Checked<Form<FormData>>) -> HttpResponse {
+    match form.report() {
+      Some(report) => HttpResponse::Ok().body(format!("{} {}", form.age, report)),
+      None => HttpResponse::Ok().body("valid"),
+    }
+  }
+
+  async fn test_json_handler(json: Checked<Json<FormData>>) -> HttpResponse {
+    let (json, report) = json.into_parts();
+    match report {
+      Some(_) => HttpResponse::Ok().body(format!("invalid {}", json.age)),
+      None => HttpResponse::Ok().body("valid"),
+    }
+  }
+
+  async fn test_query_handler(query: Checked<Query<FormData>>) -> HttpResponse {
+    HttpResponse::Ok().body(query.is_valid().to_string())
+  }
+
+  #[tokio::test]
+  async fn test_checked_form() {
+    let app = init_service(App::new().service(resource("/").route(post().to(test_form_handler)))).await;
+
+    let req = TestRequest::post()
+      .uri("/")
+      .set_form(&FormData { age: 24 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(read_body(resp).await, "valid");
+
+    let req = TestRequest::post()
+      .uri("/")
+      .set_form(&FormData { age: 30 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(read_body(resp).await, "30 age: greater than 28\n");
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+      .set_payload("age=abc")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+  }
+
+  #[tokio::test]
+  async fn test_checked_json_and_query() {
+    let app = init_service(
+      App::new()
+        .service(resource("/json").route(post().to(test_json_handler)))
+        .service(resource("/query").route(get().to(test_query_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post()
+      .uri("/json")
+      .set_json(&FormData { age: 30 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(read_body(resp).await, "invalid 30");
+
+    let req = TestRequest::post()
+      .uri("/json")
+      .insert_header((CONTENT_TYPE, "application/json"))
+      .set_payload("{\"age\": -1}")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = TestRequest::get().uri("/query?age=30").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(read_body(resp).await, "false");
+
+    let req = TestRequest::get().uri("/query?age=abc").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+  }
+}
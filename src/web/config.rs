@@ -0,0 +1,290 @@
+use crate::error::{ErrorCause, ErrorFormat, Source};
+use crate::web::either::record_branch_failure;
+use crate::web::{ExtractorMeta, PathConfig, ResponseValidation, ValidationErrorHandler};
+use actix_web::error::ErrorBadRequest;
+use actix_web::http::StatusCode;
+use actix_web::{Error, HttpRequest, ResponseError, web};
+use std::any::type_name;
+use std::sync::Arc;
+
+pub(crate) type ErrorHandler = Arc<dyn ValidationErrorHandler + Send + Sync>;
+
+/// App wide configuration consulted by every extractor
+///
+/// Extractor configs (`JsonConfig`, `QueryConfig`...) take precedence: their error handler is used when provided.
+/// Otherwise this config error handler is used, and the response is built from the status policy and the error format when no handler is set at all.
+///
+/// By default validation errors are answered with a 400 and other errors keep their own status code, Path answers with
+/// a 404, see [`PathConfig`].
+///
+/// ```rust
+/// use actix_web::App;
+/// use actix_web::http::StatusCode;
+/// use garde_actix_web::error::ErrorFormat;
+/// use garde_actix_web::web::GardeConfig;
+///
+/// let app = App::new().app_data(
+///   GardeConfig::default()
+///     .validation_status(StatusCode::UNPROCESSABLE_ENTITY)
+///     .error_format(ErrorFormat::Json),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct GardeConfig {
+  err_handler: Option<ErrorHandler>,
+  validation_status: Option<StatusCode>,
+  deserialization_status: Option<StatusCode>,
+  error_format: Option<ErrorFormat>,
+  strict_context: bool,
+  response_validation: ResponseValidation,
+}
+
+impl GardeConfig {
+  /// Error handler used by extractors whose config has none
+  pub fn error_handler<F>(mut self, f: F) -> Self
+  where
+    F: Fn(crate::error::Error, &HttpRequest) -> Error + Send + Sync + 'static,
+  {
+    self.err_handler = Some(Arc::new(f));
+    self
+  }
+
+  /// Error handler used by extractors whose config has none, receiving the extractor metadata
+  pub fn handler<H>(mut self, handler: H) -> Self
+  where
+    H: ValidationErrorHandler + Send + Sync + 'static,
+  {
+    self.err_handler = Some(Arc::new(handler));
+    self
+  }
+
+  /// Status code of validation failures
+  pub fn validation_status(mut self, status: StatusCode) -> Self {
+    self.validation_status = Some(status);
+    self
+  }
+
+  /// Status code of deserialization failures, see [`Error::is_deserialization`](crate::error::Error::is_deserialization)
+  pub fn deserialization_status(mut self, status: StatusCode) -> Self {
+    self.deserialization_status = Some(status);
+    self
+  }
+
+  /// Body format of error responses, instead of the [`ErrorFormat`] registered through `app_data`
+  pub fn error_format(mut self, error_format: ErrorFormat) -> Self {
+    self.error_format = Some(error_format);
+    self
+  }
+
+  /// Rejects requests whose validation context is neither provided nor registered with a 500, instead of using the fallback context
+  pub fn strict_context(mut self, strict_context: bool) -> Self {
+    self.strict_context = strict_context;
+    self
+  }
+
+  pub fn is_strict_context(&self) -> bool {
+    self.strict_context
+  }
+
+  /// Behaviour of [`ValidatedJson`](crate::web::ValidatedJson) responses holding invalid data
+  pub fn response_validation(mut self, response_validation: ResponseValidation) -> Self {
+    self.response_validation = response_validation;
+    self
+  }
+
+  pub(crate) fn response_validation_mode(&self) -> ResponseValidation {
+    self.response_validation
+  }
+
+  pub fn from_req(req: &HttpRequest) -> &Self {
+    req
+      .app_data::<Self>()
+      .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
+      .unwrap_or(&DEFAULT_CONFIG)
+  }
+
+  fn status(&self, error: &crate::error::Error, source: Source, req: &HttpRequest) -> StatusCode {
+    let path_policy = match source {
+      Source::Path => PathConfig::from_req(req).status(error),
+      _ => None,
+    };
+    let policy = path_policy.or_else(|| match error.as_cause() {
+      ErrorCause::ValidationError(_) => self.validation_status,
+      _ if error.is_deserialization() => self.deserialization_status,
+      _ => None,
+    });
+
+    policy.unwrap_or_else(|| match source {
+      Source::Path => StatusCode::NOT_FOUND,
+      _ => error.status_code(),
+    })
+  }
+}
+
+const DEFAULT_CONFIG: GardeConfig = GardeConfig {
+  err_handler: None,
+  validation_status: None,
+  deserialization_status: None,
+  error_format: None,
+  strict_context: false,
+  response_validation: ResponseValidation::Reject,
+};
+
+impl Default for GardeConfig {
+  fn default() -> Self {
+    DEFAULT_CONFIG
+  }
+}
+
+/// Maps an error raised while extracting `T` through the extractor handler, falling back to the app wide `GardeConfig`
+pub(crate) fn handle_error<T>(
+  error: crate::error::Error,
+  req: &HttpRequest,
+  source: Source,
+  err_handler: Option<&dyn ValidationErrorHandler>,
+) -> Error {
+  let config = GardeConfig::from_req(req);
+  let meta = ExtractorMeta::new(source, type_name::<T>(), config, req);
+
+  // deserialization failures of `Either` branches are reported by the `Either`, once every branch failed
+  let Some(mut error) = record_branch_failure(error.with_origin(source), req) else {
+    return ErrorBadRequest("Either branch failed");
+  };
+  let global_handler = config.err_handler.as_deref().map(|h| h as _);
+  for err_handler in [err_handler, global_handler].into_iter().flatten() {
+    match err_handler.handle(error, req, &meta) {
+      Ok(err) => return err,
+      Err(declined) => error = declined,
+    }
+  }
+
+  let status = config.status(&error, source, req);
+  let error_format = config
+    .error_format
+    .as_ref()
+    .unwrap_or_else(|| ErrorFormat::from_req(req));
+  error_format.respond(error, req, status)
+}
+
+#[cfg(test)]
+mod test {
+  use crate::error::{Location, Source};
+  use crate::web::{GardeConfig, Json, Query, QueryConfig};
+  use actix_http::StatusCode;
+  use actix_web::error::InternalError;
+  use actix_web::test::{TestRequest, call_service, init_service};
+  use actix_web::web::{post, resource};
+  use actix_web::{App, HttpResponse};
+  use garde::Validate;
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Debug, PartialEq, Validate, Serialize, Deserialize)]
+  struct AgeData {
+    #[garde(range(min = 18, max = 28))]
+    age: u8,
+  }
+
+  async fn test_json_handler(_: Json<AgeData>) -> HttpResponse {
+    HttpResponse::Ok().finish()
+  }
+
+  async fn test_query_handler(_: Query<AgeData>) -> HttpResponse {
+    HttpResponse::Ok().finish()
+  }
+
+  #[tokio::test]
+  async fn test_status_policy() {
+    let app = init_service(
+      App::new()
+        .app_data(
+          GardeConfig::default()
+            .validation_status(StatusCode::UNPROCESSABLE_ENTITY)
+            .deserialization_status(StatusCode::IM_A_TEAPOT),
+        )
+        .service(resource("/").route(post().to(test_json_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post().uri("/").set_json(&AgeData { age: 30 }).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header(("content-type", "application/json"))
+      .set_payload("{\"age\": \"abc\"}")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::IM_A_TEAPOT);
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header(("content-type", "text/plain"))
+      .set_payload("{\"age\": 24}")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+  }
+
+  #[tokio::test]
+  async fn test_extractor_config_overrides_global_handler() {
+    let app = init_service(
+      App::new()
+        .app_data(
+          GardeConfig::default()
+            .error_handler(|err, _req| InternalError::from_response(err, HttpResponse::Conflict().finish()).into()),
+        )
+        .service(resource("/json").route(post().to(test_json_handler)))
+        .service(
+          resource("/query")
+            .app_data(
+              QueryConfig::default()
+                .error_handler(|err, _req| InternalError::from_response(err, HttpResponse::Gone().finish()).into()),
+            )
+            .route(post().to(test_query_handler)),
+        ),
+    )
+    .await;
+
+    let req = TestRequest::post()
+      .uri("/json")
+      .set_json(&AgeData { age: 30 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    let req = TestRequest::post().uri("/query?age=30").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::GONE);
+  }
+
+  #[tokio::test]
+  async fn test_error_origin() {
+    let app = init_service(
+      App::new()
+        .app_data(GardeConfig::default().error_handler(|err, _req| {
+          let status = match (err.origin(), err.location()) {
+            (Some(Source::Query), Some(Location::Query)) => StatusCode::GONE,
+            (Some(Source::Json), Some(Location::Body)) => StatusCode::CONFLICT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+          };
+          InternalError::new(err, status).into()
+        }))
+        .service(resource("/json").route(post().to(test_json_handler)))
+        .service(resource("/query").route(post().to(test_query_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post()
+      .uri("/json")
+      .insert_header(("content-type", "application/json"))
+      .set_payload("{\"age\": \"abc\"}")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    let req = TestRequest::post().uri("/query?age=30").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::GONE);
+  }
+}
@@ -1,21 +1,130 @@
 use super::form::Form;
 use super::json::Json;
+use crate::error::{ErrorCause, Source};
+use crate::web::buffer::BufferedBody;
+use crate::web::config::{ErrorHandler, handle_error};
+use crate::web::{FormConfig, GardeExtractor, JsonConfig, ValidationErrorHandler};
+use actix_web::body::EitherBody;
 use actix_web::dev::Payload;
 use actix_web::web::Bytes;
-use actix_web::{Error, FromRequest, HttpRequest};
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest, HttpResponse, Responder, web};
 use futures::ready;
 use pin_project_lite::pin_project;
 use std::future::Future;
 use std::mem;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::Context;
 use std::task::Poll;
 
-/// Drop in replacement for [actix_web::web::Either](https://docs.rs/actix-web/latest/actix_web/web/enum.Either.html)
-#[derive(Debug, PartialEq, Eq)]
-pub enum Either<L, R> {
-  Left(L),
-  Right(R),
+macro_rules! either_n {
+  ($(#[$meta:meta])* $name:ident, $branch:ident, $proj:ident, $($variant:ident($ty:ident) = $index:literal),+) => {
+    $(#[$meta])*
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum $name<$($ty),+> {
+      $($variant($ty)),+
+    }
+
+    pin_project! {
+        /// Branch of the extractor being tried on the buffered body
+        #[doc(hidden)]
+        #[project = $proj]
+        pub enum $branch<$($ty),+>
+        where
+            $($ty: FromRequest),+
+        {
+            $($variant {
+                #[pin]
+                fut: <$ty as FromRequest>::Future,
+            }),+
+        }
+    }
+
+    impl<$($ty),+> EitherBranches for $name<$($ty),+>
+    where
+      $($ty: FromRequest + 'static),+
+    {
+      type Branch = $branch<$($ty),+>;
+
+      fn branch(index: usize, req: &HttpRequest, body: Bytes) -> Option<Self::Branch> {
+        match index {
+          $($index => Some($branch::$variant {
+            fut: $ty::from_request(req, &mut payload_from_bytes(body)),
+          }),)+
+          _ => None,
+        }
+      }
+
+      fn poll_branch(branch: Pin<&mut Self::Branch>, cx: &mut Context<'_>) -> Poll<Result<Self, Error>> {
+        let res = match branch.project() {
+          $($proj::$variant { fut } => ready!(fut.poll(cx)).map($name::$variant).map_err(Into::into),)+
+        };
+
+        Poll::Ready(res)
+      }
+    }
+
+    impl<$($ty),+> FromRequest for $name<$($ty),+>
+    where
+      Self: EitherBranches,
+    {
+      type Error = Error;
+      type Future = EitherExtractFut<Self>;
+
+      fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        EitherExtractFut::new(req, payload)
+      }
+    }
+  };
+}
+
+either_n!(
+  /// Drop in replacement for [actix_web::web::Either](https://docs.rs/actix-web/latest/actix_web/web/enum.Either.html)
+  ///
+  /// The body is buffered once, then the right branch is only tried when the left one cannot deserialize it: a
+  /// validation failure of the left branch is reported directly.
+  ///
+  /// Branches can be any extractor. Only the extractors of this crate tell deserialization and validation failures
+  /// apart, the errors of other extractors are handled as deserialization failures.
+  Either,
+  EitherBranch,
+  EitherBranchProj,
+  Left(L) = 0,
+  Right(R) = 1
+);
+either_n!(
+  /// Extractor trying three extractors in order, see [`Either`]
+  Either3,
+  Either3Branch,
+  Either3BranchProj,
+  First(A) = 0,
+  Second(B) = 1,
+  Third(C) = 2
+);
+either_n!(
+  /// Extractor trying four extractors in order, see [`Either`]
+  Either4,
+  Either4Branch,
+  Either4BranchProj,
+  First(A) = 0,
+  Second(B) = 1,
+  Third(C) = 2,
+  Fourth(D) = 3
+);
+
+impl<L, R> Responder for Either<L, R>
+where
+  L: Responder,
+  R: Responder,
+{
+  type Body = EitherBody<L::Body, R::Body>;
+
+  fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+    match self {
+      Either::Left(left) => left.respond_to(req).map_into_left_body(),
+      Either::Right(right) => right.respond_to(req).map_into_right_body(),
+    }
+  }
 }
 
 impl<T> Either<Form<T>, Json<T>> {
@@ -36,12 +145,36 @@ impl<T> Either<Json<T>, Form<T>> {
   }
 }
 
+macro_rules! into_inner {
+  ($name:ident, $($variant:ident($ty:ident)),+) => {
+    impl<T, $($ty),+> $name<$($ty),+>
+    where
+      $($ty: GardeExtractor<Inner = T>),+
+    {
+      pub fn into_inner(self) -> T {
+        match self {
+          $($name::$variant(extracted) => extracted.into_value()),+
+        }
+      }
+    }
+  };
+}
+
+into_inner!(Either3, First(A), Second(B), Third(C));
+into_inner!(Either4, First(A), Second(B), Third(C), Fourth(D));
+
+/// Error of the [`Either`] extractor before it told deserialization and validation failures apart
+///
+/// `Either` now reports an `actix_web::Error`: the validation error of the branch which deserialized the payload, or
+/// an [`ErrorCause::EitherError`] holding the error of every branch.
+#[deprecated(note = "`Either` reports `actix_web::Error`, see `EitherConfig` for the errors of its branches")]
 #[derive(Debug)]
 pub enum EitherExtractError<L, R> {
   Bytes(Error),
   Extract(L, R),
 }
 
+#[allow(deprecated)]
 impl<L, R> From<EitherExtractError<L, R>> for Error
 where
   L: Into<Error>,
@@ -55,119 +188,543 @@ where
   }
 }
 
-impl<L, R> FromRequest for Either<L, R>
-where
-  L: FromRequest + 'static,
-  R: FromRequest + 'static,
-{
-  type Error = EitherExtractError<L::Error, R::Error>;
-  type Future = EitherExtractFut<L, R>;
+/// Branches of the [`Either`], [`Either3`] and [`Either4`] extractors
+pub trait EitherBranches: Sized + 'static {
+  #[doc(hidden)]
+  type Branch;
 
-  fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
-    EitherExtractFut {
-      req: req.clone(),
-      state: EitherExtractState::Bytes {
-        bytes: Bytes::from_request(req, payload),
-      },
+  /// Starts the branch at `index` on the buffered body, `None` past the last branch
+  #[doc(hidden)]
+  fn branch(index: usize, req: &HttpRequest, body: Bytes) -> Option<Self::Branch>;
+
+  #[doc(hidden)]
+  fn poll_branch(branch: Pin<&mut Self::Branch>, cx: &mut Context<'_>) -> Poll<Result<Self, Error>>;
+}
+
+/// Failure of the branch being tried, recorded in the request extensions by the extractors of this crate
+pub(crate) enum BranchFailure {
+  /// The payload could not be deserialized, the error is left to the [`EitherConfig`] error handler
+  Deserialization(crate::error::Error),
+  /// The payload was deserialized, the error was handled by the branch
+  Validation,
+}
+
+/// Marks the request as read by a branch, see [`record_branch_failure`]
+#[derive(Default)]
+struct BranchAttempt(Option<BranchFailure>);
+
+/// Records the failure of an extractor run as a branch, handing back the errors the extractor must still handle
+///
+/// Deserialization failures are kept for the [`ErrorCause::EitherError`] raised once every branch failed.
+pub(crate) fn record_branch_failure(error: crate::error::Error, req: &HttpRequest) -> Option<crate::error::Error> {
+  let mut extensions = req.extensions_mut();
+  let Some(attempt) = extensions.get_mut::<BranchAttempt>() else {
+    return Some(error);
+  };
+
+  match error.as_cause() {
+    ErrorCause::ValidationError(_) | ErrorCause::MissingContext(_) => {
+      attempt.0 = Some(BranchFailure::Validation);
+      Some(error)
+    }
+    _ => {
+      attempt.0 = Some(BranchFailure::Deserialization(error));
+      None
     }
   }
 }
 
 pin_project! {
-    pub struct EitherExtractFut<L, R>
+    /// Future of the [`Either`], [`Either3`] and [`Either4`] extractors
+    ///
+    /// Buffers the body, then tries the branches in order until one deserializes it. When none does, the branch errors
+    /// are reported together as an [`ErrorCause::EitherError`] through the [`EitherConfig`] error handler.
+    pub struct EitherExtractFut<E>
     where
-        R: FromRequest,
-        L: FromRequest,
+        E: EitherBranches,
     {
         req: HttpRequest,
+        body: Bytes,
+        index: usize,
+        errors: Vec<crate::error::Error>,
+        other_error: Option<Error>,
         #[pin]
-        state: EitherExtractState<L, R>,
+        state: EitherExtractState<E>,
     }
 }
 
 pin_project! {
     #[project = EitherExtractProj]
-    pub enum EitherExtractState<L, R>
+    enum EitherExtractState<E>
     where
-        L: FromRequest,
-        R: FromRequest,
+        E: EitherBranches,
     {
         Bytes {
-            #[pin]
-            bytes: <Bytes as FromRequest>::Future,
+            body: BufferedBody,
         },
-        Left {
+        Branch {
             #[pin]
-            left: L::Future,
-            fallback: Bytes,
-        },
-        Right {
-            #[pin]
-            right: R::Future,
-            left_err: Option<L::Error>,
+            branch: E::Branch,
         },
     }
 }
 
-impl<R, RF, RE, L, LF, LE> Future for EitherExtractFut<L, R>
-where
-  L: FromRequest<Future = LF, Error = LE>,
-  R: FromRequest<Future = RF, Error = RE>,
-  LF: Future<Output = Result<L, LE>> + 'static,
-  RF: Future<Output = Result<R, RE>> + 'static,
-  LE: Into<Error>,
-  RE: Into<Error>,
-{
-  type Output = Result<Either<L, R>, EitherExtractError<LE, RE>>;
+impl<E: EitherBranches> EitherExtractFut<E> {
+  fn new(req: &HttpRequest, payload: &mut Payload) -> Self {
+    let limit = EitherConfig::from_req(req).limit.unwrap_or_else(|| body_limit(req));
+
+    EitherExtractFut {
+      req: req.clone(),
+      body: Bytes::new(),
+      index: 0,
+      errors: Vec::new(),
+      other_error: None,
+      state: EitherExtractState::Bytes {
+        body: BufferedBody::encoded(req, payload, Source::Either, limit),
+      },
+    }
+  }
+}
+
+impl<E: EitherBranches> Future for EitherExtractFut<E> {
+  type Output = Result<E, Error>;
 
   fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
     let mut this = self.project();
-    let ready = loop {
-      let next = match this.state.as_mut().project() {
-        EitherExtractProj::Bytes { bytes } => {
-          let res = ready!(bytes.poll(cx));
-          match res {
-            Ok(bytes) => {
-              let fallback = bytes.clone();
-              let left = L::from_request(this.req, &mut payload_from_bytes(bytes));
-              EitherExtractState::Left { left, fallback }
-            }
-            Err(err) => break Err(EitherExtractError::Bytes(err)),
+    loop {
+      match this.state.as_mut().project() {
+        EitherExtractProj::Bytes { body } => match ready!(Pin::new(body).poll(cx)) {
+          Ok(body) => *this.body = body.freeze(),
+          Err(err) => {
+            let err_handler = EitherConfig::from_req(this.req).err_handler.as_deref().map(|h| h as _);
+            return Poll::Ready(Err(handle_error::<E>(err, this.req, Source::Either, err_handler)));
           }
-        }
-        EitherExtractProj::Left { left, fallback } => {
-          let res = ready!(left.poll(cx));
-          match res {
-            Ok(extracted) => break Ok(Either::Left(extracted)),
-            Err(left_err) => {
-              let right = R::from_request(this.req, &mut payload_from_bytes(mem::take(fallback)));
-              EitherExtractState::Right {
-                left_err: Some(left_err),
-                right,
-              }
+        },
+        EitherExtractProj::Branch { branch } => {
+          let res = ready!(E::poll_branch(branch, cx));
+          let attempt = this.req.extensions_mut().remove::<BranchAttempt>();
+          match (res, attempt.and_then(|attempt| attempt.0)) {
+            (Ok(extracted), _) => return Poll::Ready(Ok(extracted)),
+            (Err(err), Some(BranchFailure::Validation)) => return Poll::Ready(Err(err)),
+            (Err(_), Some(BranchFailure::Deserialization(err))) => this.errors.push(err),
+            (Err(err), None) => {
+              this.other_error.get_or_insert(err);
             }
           }
+          *this.index += 1;
         }
-        EitherExtractProj::Right { right, left_err } => {
-          let res = ready!(right.poll(cx));
-          match res {
-            Ok(data) => break Ok(Either::Right(data)),
-            Err(err) => {
-              #[allow(clippy::unwrap_used)]
-              break Err(EitherExtractError::Extract(left_err.take().unwrap(), err));
-            }
+      }
+
+      this.req.extensions_mut().insert(BranchAttempt::default());
+      match E::branch(*this.index, this.req, this.body.clone()) {
+        Some(branch) => this.state.set(EitherExtractState::Branch { branch }),
+        None => {
+          this.req.extensions_mut().remove::<BranchAttempt>();
+          log::debug!(
+            "Failed to deserialize Either from payload. \
+                         Request path: {}",
+            this.req.path()
+          );
+
+          if let Some(err) = this.other_error.take() {
+            return Poll::Ready(Err(err));
           }
-        }
-      };
-      this.state.set(next);
-    };
 
-    Poll::Ready(ready)
+          let err = crate::error::Error::new(ErrorCause::EitherError(mem::take(this.errors)), Source::Either);
+          let err_handler = EitherConfig::from_req(this.req).err_handler.as_deref().map(|h| h as _);
+          return Poll::Ready(Err(handle_error::<E>(err, this.req, Source::Either, err_handler)));
+        }
+      }
+    }
   }
 }
 
+/// Largest body limit of the extractor configs
+fn body_limit(req: &HttpRequest) -> usize {
+  let limit = JsonConfig::from_req(req).limit.max(FormConfig::from_req(req).limit);
+  #[cfg(feature = "serde_qs")]
+  let limit = limit.max(crate::web::qs::form_limit(req));
+
+  limit
+}
+
 fn payload_from_bytes(bytes: Bytes) -> Payload {
   let (_, mut h1_payload) = actix_http::h1::Payload::create(true);
   h1_payload.unread_data(bytes);
   Payload::from(h1_payload)
 }
+
+/// Configuration of the [`Either`] extractor
+///
+/// Without explicit limit, the body is buffered up to the largest limit of the body extractor configs
+/// ([`JsonConfig`], [`FormConfig`], `QsQueryConfig`), each branch then applying its own.
+/// The error handler receives the errors raised while buffering and the [`ErrorCause::EitherError`] raised when no
+/// branch deserializes the payload. Validation errors go through the config of the branch which deserialized it.
+///
+/// The errors of branches which are not extractors of this crate are not collected: when one of them failed, the
+/// error of the first one is returned as is.
+#[derive(Clone, Default)]
+pub struct EitherConfig {
+  limit: Option<usize>,
+  err_handler: Option<ErrorHandler>,
+}
+
+impl EitherConfig {
+  pub fn limit(mut self, limit: usize) -> Self {
+    self.limit = Some(limit);
+    self
+  }
+
+  pub fn error_handler<F>(mut self, f: F) -> Self
+  where
+    F: Fn(crate::error::Error, &HttpRequest) -> Error + Send + Sync + 'static,
+  {
+    self.err_handler = Some(Arc::new(f));
+    self
+  }
+
+  /// Error handler receiving the extractor metadata, see [`ValidationErrorHandler`]
+  pub fn handler<H>(mut self, handler: H) -> Self
+  where
+    H: ValidationErrorHandler + Send + Sync + 'static,
+  {
+    self.err_handler = Some(Arc::new(handler));
+    self
+  }
+
+  pub fn from_req(req: &HttpRequest) -> &Self {
+    req
+      .app_data::<Self>()
+      .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
+      .unwrap_or(&DEFAULT_CONFIG)
+  }
+}
+
+const DEFAULT_CONFIG: EitherConfig = EitherConfig {
+  limit: None,
+  err_handler: None,
+};
+
+#[cfg(test)]
+mod test {
+  use crate::error::ErrorFormat;
+  use crate::error::{ErrorCause, Source};
+  use crate::web::{Either, Either3, EitherConfig, Form, FormConfig, GardeConfig, Json, JsonConfig, Query};
+  use actix_http::StatusCode;
+  use actix_http::header::{CONTENT_ENCODING, CONTENT_TYPE};
+  use actix_web::FromRequest;
+  use actix_web::error::InternalError;
+  use actix_web::test::{TestRequest, call_service, init_service, read_body, read_body_json};
+  use actix_web::web::{post, resource};
+  use actix_web::{App, HttpResponse};
+  use flate2::Compression;
+  use flate2::write::GzEncoder;
+  use garde::Validate;
+  use serde::{Deserialize, Serialize};
+  use std::io::Write;
+
+  #[derive(Debug, PartialEq, Validate, Serialize, Deserialize)]
+  struct EitherData {
+    #[garde(range(min = 18, max = 28))]
+    age: u8,
+  }
+
+  async fn test_handler(data: Either<Json<EitherData>, Form<EitherData>>) -> HttpResponse {
+    match data {
+      Either::Left(_) => HttpResponse::Ok().body("json"),
+      Either::Right(_) => HttpResponse::Ok().body("form"),
+    }
+  }
+
+  #[tokio::test]
+  async fn test_either_validation() {
+    let app = init_service(App::new().service(resource("/").route(post().to(test_handler)))).await;
+
+    let req = TestRequest::post()
+      .uri("/")
+      .set_json(EitherData { age: 24 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = TestRequest::post()
+      .uri("/")
+      .set_form(EitherData { age: 24 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = TestRequest::post()
+      .uri("/")
+      .set_form(EitherData { age: 30 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+  }
+
+  #[tokio::test]
+  async fn test_either_does_not_fall_back_on_validation_error() {
+    let app = init_service(
+      App::new()
+        .app_data(
+          JsonConfig::default()
+            .error_handler(|err, _req| InternalError::from_response(err, HttpResponse::Conflict().finish()).into()),
+        )
+        .app_data(
+          FormConfig::default()
+            .error_handler(|err, _req| InternalError::from_response(err, HttpResponse::Gone().finish()).into()),
+        )
+        .service(resource("/").route(post().to(test_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post()
+      .uri("/")
+      .set_json(EitherData { age: 30 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+  }
+
+  #[tokio::test]
+  async fn test_either_both_failed() {
+    let app = init_service(
+      App::new()
+        .app_data(EitherConfig::default().error_handler(|err, _req| {
+          let status = match err.as_cause() {
+            ErrorCause::EitherError(errors)
+              if matches!(errors[..], [ref left, ref right]
+                if matches!(left.as_cause(), ErrorCause::JsonPayloadError(_))
+                  && matches!(right.as_cause(), ErrorCause::UrlencodedError(_))
+                  && left.origin() == Some(Source::Json)
+                  && right.origin() == Some(Source::Form)) =>
+            {
+              StatusCode::IM_A_TEAPOT
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+          };
+          InternalError::new(err, status).into()
+        }))
+        .service(resource("/").route(post().to(test_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+      .set_payload("age=abc")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::IM_A_TEAPOT);
+
+    let app = init_service(
+      App::new()
+        .app_data(GardeConfig::default().error_format(ErrorFormat::Json))
+        .service(resource("/").route(post().to(test_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+      .set_payload("age=abc")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = read_body_json(resp).await;
+    let sources: Vec<&str> = body["errors"]
+      .as_array()
+      .into_iter()
+      .flatten()
+      .filter_map(|error| error["source"].as_str())
+      .collect();
+    assert_eq!(sources, ["json", "form"]);
+  }
+
+  #[derive(Debug, PartialEq, Validate, Serialize, Deserialize)]
+  struct LargeData {
+    #[garde(length(min = 1))]
+    name: String,
+  }
+
+  async fn test_large_handler(_: Either<Json<LargeData>, Form<LargeData>>) -> HttpResponse {
+    HttpResponse::Ok().finish()
+  }
+
+  #[tokio::test]
+  async fn test_either_limits() {
+    let app = init_service(App::new().service(resource("/").route(post().to(test_large_handler)))).await;
+
+    let data = LargeData {
+      name: "a".repeat(300_000),
+    };
+    let req = TestRequest::post().uri("/").set_json(&data).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let app = init_service(
+      App::new()
+        .app_data(JsonConfig::default().limit(1_000))
+        .service(resource("/").route(post().to(test_large_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post().uri("/").set_json(&data).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+  }
+
+  #[tokio::test]
+  async fn test_either_custom_config() {
+    let app = init_service(
+      App::new()
+        .app_data(
+          EitherConfig::default()
+            .limit(8)
+            .error_handler(|err, _req| InternalError::from_response(err, HttpResponse::Conflict().finish()).into()),
+        )
+        .service(resource("/").route(post().to(test_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post()
+      .uri("/")
+      .set_form(EitherData { age: 24 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = TestRequest::post()
+      .uri("/")
+      .set_json(EitherData { age: 24 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+  }
+
+  async fn test_bytes_handler(data: Either<Json<EitherData>, actix_web::web::Bytes>) -> HttpResponse {
+    match data {
+      Either::Left(_) => HttpResponse::Ok().body("json"),
+      Either::Right(bytes) => HttpResponse::Ok().body(bytes),
+    }
+  }
+
+  #[tokio::test]
+  async fn test_either_other_extractor() {
+    let app = init_service(App::new().service(resource("/").route(post().to(test_bytes_handler)))).await;
+
+    let req = TestRequest::post()
+      .uri("/")
+      .set_json(EitherData { age: 24 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(read_body(resp).await, "json");
+
+    let req = TestRequest::post()
+      .uri("/")
+      .set_json(EitherData { age: 30 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = TestRequest::post().uri("/").set_payload("age=24").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(read_body(resp).await, "age=24");
+  }
+
+  #[tokio::test]
+  async fn test_either_compressed() {
+    let app = init_service(App::new().service(resource("/").route(post().to(test_handler)))).await;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(b"{\"age\": 24}").unwrap();
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "application/json"))
+      .insert_header((CONTENT_ENCODING, "gzip"))
+      .set_payload(encoder.finish().unwrap())
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(read_body(resp).await, "json");
+  }
+
+  async fn test_either3_handler(data: Either3<Json<EitherData>, Form<EitherData>, Query<EitherData>>) -> HttpResponse {
+    let branch = match data {
+      Either3::First(_) => "json",
+      Either3::Second(_) => "form",
+      Either3::Third(_) => "query",
+    };
+    HttpResponse::Ok().body(branch)
+  }
+
+  #[tokio::test]
+  async fn test_either3() {
+    let app = init_service(App::new().service(resource("/").route(post().to(test_either3_handler)))).await;
+
+    let req = TestRequest::post()
+      .uri("/")
+      .set_json(EitherData { age: 24 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(read_body(resp).await, "json");
+
+    let req = TestRequest::post()
+      .uri("/")
+      .set_form(EitherData { age: 24 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(read_body(resp).await, "form");
+
+    let req = TestRequest::post().uri("/?age=24").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(read_body(resp).await, "query");
+
+    let req = TestRequest::post().uri("/?age=30").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let (req, mut payload) = TestRequest::post().uri("/?age=abc").to_http_parts();
+    let res = Either3::<Json<EitherData>, Form<EitherData>, Query<EitherData>>::from_request(&req, &mut payload).await;
+    let err = res.err();
+    let err = err.as_ref().and_then(|err| err.as_error::<crate::error::Error>());
+    assert!(matches!(err.map(|err| err.as_cause()), Some(ErrorCause::EitherError(errors)) if errors.len() == 3));
+
+    let (req, mut payload) = TestRequest::post().uri("/?age=24").to_http_parts();
+    let res = Either3::<Json<EitherData>, Form<EitherData>, Query<EitherData>>::from_request(&req, &mut payload).await;
+    assert_eq!(res.map(Either3::into_inner).ok(), Some(EitherData { age: 24 }));
+  }
+
+  #[cfg(feature = "serde_qs")]
+  #[tokio::test]
+  async fn test_either3_qs_form() {
+    use crate::web::QsForm;
+
+    #[derive(Debug, Validate, Deserialize)]
+    struct Nested {
+      #[garde(dive)]
+      data: EitherData,
+    }
+
+    async fn nested_handler(data: Either3<Json<Nested>, Form<Nested>, QsForm<Nested>>) -> HttpResponse {
+      HttpResponse::Ok().body(data.into_inner().data.age.to_string())
+    }
+
+    let app = init_service(App::new().service(resource("/").route(post().to(nested_handler)))).await;
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+      .set_payload("data[age]=24")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(read_body(resp).await, "24");
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+      .set_payload("data[age]=30")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+  }
+}
@@ -0,0 +1,165 @@
+use crate::async_validate::{has_async_validator, validate_async_for_request};
+use crate::context::ContextFallback;
+use crate::error::{Location, Source};
+use crate::validate_for_request;
+use crate::web::ValidationErrorHandler;
+use crate::web::config::handle_error;
+use actix_web::dev::Payload;
+use actix_web::{Error, HttpRequest};
+use futures::future::LocalBoxFuture;
+use futures::{FutureExt, ready};
+use garde::Validate;
+use pin_project_lite::pin_project;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Extractors of this crate, whose deserialization can run apart from validation
+///
+/// Used by the extractors wrapping another one, such as [`Checked`](crate::web::Checked).
+pub trait GardeExtractor: Sized {
+  /// Extracted type, validated by `garde`
+  type Inner: Validate + 'static;
+  type InnerFuture: Future<Output = Result<Self::Inner, crate::error::Error>> + 'static;
+
+  const SOURCE: Source;
+
+  /// Deserializes the inner value, without validating it
+  fn extract_inner(req: &HttpRequest, payload: &mut Payload) -> Self::InnerFuture;
+
+  fn from_inner(inner: Self::Inner) -> Self;
+
+  /// Unwraps the extracted value, as the `into_inner` method of the extractor
+  fn into_value(self) -> Self::Inner;
+
+  /// Error handler of the extractor config, if any, tried before the [`GardeConfig`](crate::web::GardeConfig) one
+  fn err_handler(req: &HttpRequest) -> Option<&dyn ValidationErrorHandler>;
+}
+
+pin_project! {
+    /// Future of the extractors deserializing with [`GardeExtractor::extract_inner`], then validating the data
+    ///
+    /// Future of `Json`, `Form`, `QsForm` and `LabQuery`. Holds the only handle on the request. The
+    /// [`AsyncValidate`](crate::async_validate::AsyncValidate) hook of body extractors is boxed, only when an
+    /// `AsyncValidator` is registered.
+    ///
+    /// `Body`, `Valid`, `Checked`, `JsonBytes` and the response validation still return boxed futures.
+    pub struct ExtractFut<E>
+    where
+        E: GardeExtractor,
+    {
+        req: HttpRequest,
+        #[pin]
+        state: ExtractState<E>,
+    }
+}
+
+pin_project! {
+    /// Deserialization then validation of `E`, polled with the request it reads
+    #[doc(hidden)]
+    #[project = ExtractProj]
+    pub enum ExtractState<E>
+    where
+        E: GardeExtractor,
+    {
+        Inner {
+            #[pin]
+            inner: E::InnerFuture,
+        },
+        Async {
+            validate: LocalBoxFuture<'static, Result<E::Inner, crate::error::Error>>,
+        },
+    }
+}
+
+/// Step of an extraction which failed
+pub(crate) enum Failure {
+  /// The payload could not be deserialized
+  Deserialization(crate::error::Error),
+  /// The deserialized payload is invalid, or could not be validated
+  Validation(crate::error::Error),
+}
+
+impl Failure {
+  pub(crate) fn into_error(self) -> crate::error::Error {
+    match self {
+      Failure::Deserialization(e) | Failure::Validation(e) => e,
+    }
+  }
+}
+
+impl<E: GardeExtractor> ExtractState<E> {
+  pub(crate) fn new(req: &HttpRequest, payload: &mut Payload) -> Self {
+    ExtractState::Inner {
+      inner: E::extract_inner(req, payload),
+    }
+  }
+}
+
+impl<E> ExtractState<E>
+where
+  E: GardeExtractor,
+  <E::Inner as Validate>::Context: ContextFallback,
+{
+  pub(crate) fn poll_extract(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    req: &HttpRequest,
+  ) -> Poll<Result<E::Inner, Failure>> {
+    loop {
+      match self.as_mut().project() {
+        ExtractProj::Inner { inner } => {
+          let data = match ready!(inner.poll(cx)) {
+            Ok(data) => data,
+            Err(e) => return Poll::Ready(Err(Failure::Deserialization(e.with_origin(E::SOURCE)))),
+          };
+          match validate_for_request(data, req, E::SOURCE) {
+            Ok(data) if E::SOURCE.location() == Location::Body && has_async_validator::<E::Inner>(req) => {
+              let req = req.clone();
+              let validate = async move { validate_async_for_request(data, &req).await }.boxed_local();
+              self.set(ExtractState::Async { validate });
+            }
+            res => return Poll::Ready(res.map_err(Failure::Validation)),
+          }
+        }
+        ExtractProj::Async { validate } => {
+          let res = ready!(validate.as_mut().poll(cx));
+          return Poll::Ready(res.map_err(|e| Failure::Validation(e.with_origin(E::SOURCE))));
+        }
+      }
+    }
+  }
+}
+
+impl<E: GardeExtractor> ExtractFut<E> {
+  pub(crate) fn new(req: &HttpRequest, payload: &mut Payload) -> Self {
+    ExtractFut {
+      state: ExtractState::new(req, payload),
+      req: req.clone(),
+    }
+  }
+}
+
+impl<E> Future for ExtractFut<E>
+where
+  E: GardeExtractor,
+  <E::Inner as Validate>::Context: ContextFallback,
+{
+  type Output = Result<E, Error>;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let this = self.project();
+    let res = ready!(this.state.poll_extract(cx, this.req));
+
+    Poll::Ready(res.map(E::from_inner).map_err(|failure| {
+      log::debug!(
+        "Failed during {} extractor validation. \
+                         Request path: {}",
+        E::SOURCE,
+        this.req.path()
+      );
+
+      handle_error::<E::Inner>(failure.into_error(), this.req, E::SOURCE, E::err_handler(this.req))
+    }))
+  }
+}
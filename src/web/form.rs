@@ -1,17 +1,29 @@
 use actix_http::Payload;
+use actix_web::body::EitherBody;
+use actix_web::error::UrlencodedError;
 use actix_web::web::UrlEncoded;
-use actix_web::{Error, FromRequest, HttpRequest, web};
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest, HttpResponse, Responder, web};
+use encoding_rs::{Encoding, UTF_8};
 use serde::{Serialize, de::DeserializeOwned};
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
 use std::rc::Rc;
-
-use crate::validate_for_request;
+use std::task::{Context, Poll};
+
+use crate::context::ContextFallback;
+use crate::error::Source;
+use crate::limits::Limits;
+use crate::web::buffer::BufferedBody;
+use crate::web::config::handle_error;
+use crate::web::{ExtractFut, GardeExtractor, ValidationErrorHandler};
 use derive_more::{AsRef, Deref, DerefMut, Display, From};
-use futures::FutureExt;
-use futures::future::LocalBoxFuture;
+use futures::future::{Either, ErrInto};
+use futures::{FutureExt, TryFutureExt, ready};
 use garde::Validate;
 
 /// Drop in replacement for [actix_web::web::Form](https://docs.rs/actix-web/latest/actix_web/web/struct.Form.html)
-#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Deref, DerefMut, AsRef, Display, From)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deref, DerefMut, AsRef, Display, From)]
 pub struct Form<T>(pub T);
 
 impl<T> Form<T> {
@@ -20,64 +32,98 @@ impl<T> Form<T> {
   }
 }
 
-impl<T> Serialize for Form<T>
-where
-  T: Serialize,
-{
-  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-  where
-    S: serde::Serializer,
-  {
-    self.0.serialize(serializer)
+impl_serialize!(Form);
+
+/// Serializes the value as `application/x-www-form-urlencoded`, serialization errors go through the `FormConfig` error
+/// handler
+///
+/// Without handler, they are answered with a 500 as actix does.
+impl<T: Serialize> Responder for Form<T> {
+  type Body = EitherBody<String>;
+
+  fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+    match serde_urlencoded::to_string(&self.0) {
+      Ok(body) => match HttpResponse::Ok()
+        .content_type(mime::APPLICATION_WWW_FORM_URLENCODED)
+        .message_body(body)
+      {
+        Ok(res) => res.map_into_left_body(),
+        Err(err) => HttpResponse::from_error(err).map_into_right_body(),
+      },
+      Err(err) => {
+        let err_handler = FormConfig::from_req(req).err_handler.as_deref();
+        let err = handle_error::<T>(UrlencodedError::Serialize(err).into(), req, Source::Form, err_handler);
+        HttpResponse::from_error(err).map_into_right_body()
+      }
+    }
   }
 }
 
 impl<T> FromRequest for Form<T>
 where
   T: DeserializeOwned + Validate + 'static,
-  T::Context: Default,
+  T::Context: ContextFallback,
 {
   type Error = Error;
-  type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+  type Future = ExtractFut<Self>;
 
   #[inline]
   fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
-    let req_copy = req.clone();
-    let req_copy2 = req.clone();
-
-    let FormConfig { limit, err_handler } = FormConfig::from_req(req).clone();
-
-    UrlEncoded::new(req, payload)
-      .limit(limit)
-      .map(move |res: Result<T, _>| match res {
-        Ok(data) => {
-          let req = req_copy;
-          validate_for_request(data, &req)
-        }
-        Err(e) => Err(e.into()),
-      })
-      .map(move |res| match res {
-        Err(err) => {
-          if let Some(err_handler) = err_handler.as_ref() {
-            Err((*err_handler)(err, &req_copy2))
-          } else {
-            Err(err.into())
-          }
-        }
-        Ok(data) => Ok(Form(data)),
-      })
-      .boxed_local()
+    ExtractFut::new(req, payload)
   }
 }
 
-type FormErrHandler = Option<Rc<dyn Fn(crate::error::Error, &HttpRequest) -> Error>>;
+impl<T> GardeExtractor for Form<T>
+where
+  T: DeserializeOwned + Validate + 'static,
+{
+  type Inner = T;
+  type InnerFuture = Either<ErrInto<UrlEncoded<T>, crate::error::Error>, FormLimitedBody<T>>;
+
+  const SOURCE: Source = Source::Form;
+
+  fn extract_inner(req: &HttpRequest, payload: &mut Payload) -> Self::InnerFuture {
+    let config = FormConfig::from_req(req);
+
+    if config.limits == Limits::NONE {
+      Either::Left(UrlEncoded::new(req, payload).limit(config.limit).err_into())
+    } else {
+      let is_form = req
+        .mime_type()
+        .ok()
+        .flatten()
+        .is_some_and(|mime| mime.essence_str() == mime::APPLICATION_WWW_FORM_URLENCODED.essence_str());
+      let encoding = req.encoding().ok();
+
+      Either::Right(FormLimitedBody {
+        body: BufferedBody::new(req, payload, Source::Form, is_form && encoding.is_some(), config.limit),
+        encoding: encoding.unwrap_or(UTF_8),
+        limits: config.limits,
+        data: PhantomData,
+      })
+    }
+  }
+
+  fn from_inner(inner: T) -> Self {
+    Form(inner)
+  }
+
+  fn into_value(self) -> T {
+    self.0
+  }
+
+  fn err_handler(req: &HttpRequest) -> Option<&dyn ValidationErrorHandler> {
+    FormConfig::from_req(req).err_handler.as_deref()
+  }
+}
 
 /// Replacement for [actix_web::web::FormConfig](https://docs.rs/actix-web/latest/actix_web/web/struct.FormConfig.html)
 /// Error handler must map from an `garde_actix_web::error::Error`
 #[derive(Clone)]
 pub struct FormConfig {
-  limit: usize,
-  err_handler: FormErrHandler,
+  pub(crate) limit: usize,
+  err_handler: Option<Rc<dyn ValidationErrorHandler>>,
+  limits: Limits,
 }
 
 impl FormConfig {
@@ -86,6 +132,12 @@ impl FormConfig {
     self
   }
 
+  /// Structural limits checked while deserializing the body, see [`Limits`]
+  pub fn limits(mut self, limits: Limits) -> Self {
+    self.limits = limits;
+    self
+  }
+
   pub fn error_handler<F>(mut self, f: F) -> Self
   where
     F: Fn(crate::error::Error, &HttpRequest) -> Error + 'static,
@@ -94,7 +146,16 @@ impl FormConfig {
     self
   }
 
-  fn from_req(req: &HttpRequest) -> &Self {
+  /// Error handler receiving the extractor metadata, see [`ValidationErrorHandler`]
+  pub fn handler<H>(mut self, handler: H) -> Self
+  where
+    H: ValidationErrorHandler + 'static,
+  {
+    self.err_handler = Some(Rc::new(handler));
+    self
+  }
+
+  pub(crate) fn from_req(req: &HttpRequest) -> &Self {
     req
       .app_data::<Self>()
       .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
@@ -105,6 +166,7 @@ impl FormConfig {
 const DEFAULT_CONFIG: FormConfig = FormConfig {
   limit: 16_384, // 2^14 bytes (~16kB)
   err_handler: None,
+  limits: Limits::NONE,
 };
 
 impl Default for FormConfig {
@@ -113,13 +175,50 @@ impl Default for FormConfig {
   }
 }
 
+/// Buffers a [`Form`] body as `UrlEncoded` does, then deserializes it in its charset, checking the `FormConfig` limits
+pub struct FormLimitedBody<T> {
+  body: BufferedBody,
+  encoding: &'static Encoding,
+  limits: Limits,
+  data: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> Future for FormLimitedBody<T> {
+  type Output = Result<T, crate::error::Error>;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let this = self.get_mut();
+    let body = ready!(this.body.poll_unpin(cx))?;
+
+    let decoded;
+    let input = if this.encoding == UTF_8 {
+      &body[..]
+    } else {
+      decoded = match this.encoding.decode_without_bom_handling_and_without_replacement(&body) {
+        Some(decoded) => decoded,
+        None => return Poll::Ready(Err(UrlencodedError::Encoding.into())),
+      };
+      decoded.as_bytes()
+    };
+
+    let deserializer = serde_urlencoded::Deserializer::new(form_urlencoded::parse(input));
+    Poll::Ready(
+      this
+        .limits
+        .deserialize(deserializer, |e| UrlencodedError::Parse(e).into()),
+    )
+  }
+}
+
 #[cfg(test)]
 mod test {
+  use crate::limits::Limits;
   use crate::web::{Form, FormConfig};
   use actix_http::StatusCode;
+  use actix_http::header::CONTENT_TYPE;
   use actix_web::error::InternalError;
-  use actix_web::test::{TestRequest, call_service, init_service};
-  use actix_web::web::{post, resource};
+  use actix_web::test::{TestRequest, call_service, init_service, read_body};
+  use actix_web::web::{get, post, resource};
   use actix_web::{App, HttpResponse};
   use garde::Validate;
   use serde::{Deserialize, Serialize};
@@ -246,4 +345,124 @@ mod test {
     let resp = call_service(&app, req).await;
     assert_eq!(resp.status(), StatusCode::OK);
   }
+
+  async fn test_echo_handler(form: Form<FormData>) -> Form<FormData> {
+    form
+  }
+
+  async fn test_unserializable_handler() -> Form<u8> {
+    Form(24)
+  }
+
+  #[tokio::test]
+  async fn test_form_responder() {
+    let app = init_service(App::new().service(resource("/").route(post().to(test_echo_handler)))).await;
+
+    let req = TestRequest::post()
+      .uri("/")
+      .set_form(&FormData { age: 24 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+      resp.headers().get(CONTENT_TYPE).and_then(|h| h.to_str().ok()),
+      Some("application/x-www-form-urlencoded")
+    );
+    assert_eq!(read_body(resp).await, "age=24");
+
+    let app = init_service(
+      App::new()
+        .app_data(
+          FormConfig::default()
+            .error_handler(|err, _req| InternalError::from_response(err, HttpResponse::Conflict().finish()).into()),
+        )
+        .service(resource("/unserializable").route(get().to(test_unserializable_handler))),
+    )
+    .await;
+
+    let req = TestRequest::get().uri("/unserializable").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+  }
+
+  #[tokio::test]
+  async fn test_form_limits() {
+    let app = init_service(
+      App::new()
+        .app_data(
+          FormConfig::default()
+            .limits(Limits::default().max_fields(2).reject_duplicate_keys(true))
+            .error_handler(|err, _req| {
+              let kind = format!("{:?}", err.kind());
+              InternalError::from_response(err, HttpResponse::BadRequest().body(kind)).into()
+            }),
+        )
+        .service(resource("/").route(post().to(test_handler))),
+    )
+    .await;
+
+    let cases = [
+      ("age=24&name=netwo", StatusCode::OK, ""),
+      ("age=30", StatusCode::BAD_REQUEST, "Validation"),
+      (
+        "age=24&name=netwo&city=paris",
+        StatusCode::BAD_REQUEST,
+        "FieldCountLimit",
+      ),
+      ("age=24&age=25", StatusCode::BAD_REQUEST, "DuplicateKey"),
+      ("age=abc", StatusCode::BAD_REQUEST, "Urlencoded"),
+    ];
+    for (payload, status, kind) in cases {
+      let req = TestRequest::post()
+        .uri("/")
+        .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+        .set_payload(payload)
+        .to_request();
+      let resp = call_service(&app, req).await;
+      assert_eq!(resp.status(), status, "{payload}");
+      assert_eq!(read_body(resp).await, kind, "{payload}");
+    }
+
+    let req = TestRequest::post().uri("/").set_payload("age=24").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(read_body(resp).await, "Urlencoded");
+  }
+
+  #[derive(Debug, PartialEq, Validate, Serialize, Deserialize)]
+  struct NameData {
+    #[garde(length(max = 10))]
+    name: String,
+  }
+
+  async fn test_name_handler(form: Form<NameData>) -> String {
+    form.into_inner().name
+  }
+
+  #[tokio::test]
+  async fn test_form_limits_charset() {
+    let app = init_service(
+      App::new()
+        .app_data(FormConfig::default().limits(Limits::default().max_fields(2)))
+        .service(resource("/").route(post().to(test_name_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded; charset=iso-8859-1"))
+      .set_payload(&b"name=caf\xe9"[..])
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(read_body(resp).await, "café");
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded; charset=unknown"))
+      .set_payload("name=netwo")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+  }
 }
@@ -0,0 +1,277 @@
+use crate::error::{Location, Source};
+#[cfg(feature = "serde_qs")]
+use crate::web::QsQueryConfig;
+use crate::web::{
+  BodyConfig, EitherConfig, FormConfig, GardeConfig, HeaderConfig, JsonConfig, PathConfig, QueryConfig,
+};
+use actix_web::{Error, HttpRequest, web};
+use std::any::TypeId;
+
+/// Maps extractor errors to actix errors
+///
+/// Implemented for every `Fn(garde_actix_web::error::Error, &HttpRequest) -> actix_web::Error` closure, which always handles the error.
+///
+/// A handler may decline an error by giving it back, the next handler of the chain (see [`ValidationErrorHandler::or_else`]),
+/// then the app wide [`GardeConfig`] handler and finally the default response are tried in order.
+///
+/// ```rust
+/// use actix_web::error::InternalError;
+/// use actix_web::{Error, HttpRequest, HttpResponse};
+/// use garde_actix_web::error::Location;
+/// use garde_actix_web::web::{ExtractorMeta, JsonConfig, ValidationErrorHandler};
+///
+/// struct BodyOnly;
+///
+/// impl ValidationErrorHandler for BodyOnly {
+///   fn handle(
+///     &self,
+///     error: garde_actix_web::error::Error,
+///     _req: &HttpRequest,
+///     meta: &ExtractorMeta<'_>,
+///   ) -> Result<Error, garde_actix_web::error::Error> {
+///     match meta.location() {
+///       Location::Body => Ok(InternalError::from_response(error, HttpResponse::UnprocessableEntity().finish()).into()),
+///       _ => Err(error),
+///     }
+///   }
+/// }
+///
+/// let config = JsonConfig::default().handler(BodyOnly.or_else(|error, _req: &HttpRequest| {
+///   InternalError::from_response(error, HttpResponse::BadRequest().finish()).into()
+/// }));
+/// ```
+pub trait ValidationErrorHandler {
+  /// Maps the error, or gives it back to let the next handler deal with it
+  fn handle(
+    &self,
+    error: crate::error::Error,
+    req: &HttpRequest,
+    meta: &ExtractorMeta<'_>,
+  ) -> Result<Error, crate::error::Error>;
+
+  /// Chains a fallback handler, used when this one declines the error
+  fn or_else<H>(self, fallback: H) -> Chain<Self, H>
+  where
+    Self: Sized,
+    H: ValidationErrorHandler,
+  {
+    Chain { first: self, fallback }
+  }
+}
+
+impl<F> ValidationErrorHandler for F
+where
+  F: Fn(crate::error::Error, &HttpRequest) -> Error,
+{
+  fn handle(
+    &self,
+    error: crate::error::Error,
+    req: &HttpRequest,
+    _meta: &ExtractorMeta<'_>,
+  ) -> Result<Error, crate::error::Error> {
+    Ok((self)(error, req))
+  }
+}
+
+/// Handler trying `first`, then `fallback` when the error is declined
+///
+/// Built by [`ValidationErrorHandler::or_else`].
+#[derive(Clone, Debug)]
+pub struct Chain<A, B> {
+  first: A,
+  fallback: B,
+}
+
+impl<A, B> ValidationErrorHandler for Chain<A, B>
+where
+  A: ValidationErrorHandler,
+  B: ValidationErrorHandler,
+{
+  fn handle(
+    &self,
+    error: crate::error::Error,
+    req: &HttpRequest,
+    meta: &ExtractorMeta<'_>,
+  ) -> Result<Error, crate::error::Error> {
+    self
+      .first
+      .handle(error, req, meta)
+      .or_else(|error| self.fallback.handle(error, req, meta))
+  }
+}
+
+/// Describes the extractor which failed and the configs in effect
+#[derive(Clone, Copy)]
+pub struct ExtractorMeta<'a> {
+  source: Source,
+  type_name: &'static str,
+  config: &'a GardeConfig,
+  req: &'a HttpRequest,
+}
+
+impl<'a> ExtractorMeta<'a> {
+  pub(crate) fn new(source: Source, type_name: &'static str, config: &'a GardeConfig, req: &'a HttpRequest) -> Self {
+    Self {
+      source,
+      type_name,
+      config,
+      req,
+    }
+  }
+
+  /// Extractor kind
+  pub fn source(&self) -> Source {
+    self.source
+  }
+
+  /// Part of the request the extractor reads
+  pub fn location(&self) -> Location {
+    self.source.location()
+  }
+
+  /// Name of the extracted type, as given by [`std::any::type_name`]
+  pub fn type_name(&self) -> &'static str {
+    self.type_name
+  }
+
+  /// App wide config in effect for the request
+  pub fn config(&self) -> &'a GardeConfig {
+    self.config
+  }
+
+  /// Config of the extractor (`JsonConfig`, `QueryConfig`...) registered for the request
+  ///
+  /// `None` when the extractor runs with its default config, or when `C` is not its config type.
+  pub fn extractor_config<C: 'static>(&self) -> Option<&'a C> {
+    if config_type(self.source) != Some(TypeId::of::<C>()) {
+      return None;
+    }
+
+    self
+      .req
+      .app_data::<C>()
+      .or_else(|| self.req.app_data::<web::Data<C>>().map(|d| d.as_ref()))
+  }
+}
+
+/// Config type read by the extractors of a source
+fn config_type(source: Source) -> Option<TypeId> {
+  match source {
+    Source::Path => Some(TypeId::of::<PathConfig>()),
+    Source::Query | Source::LabQuery => Some(TypeId::of::<QueryConfig>()),
+    Source::Json => Some(TypeId::of::<JsonConfig>()),
+    Source::Form => Some(TypeId::of::<FormConfig>()),
+    Source::Header => Some(TypeId::of::<HeaderConfig>()),
+    #[cfg(feature = "serde_qs")]
+    Source::QsQuery | Source::QsForm => Some(TypeId::of::<QsQueryConfig>()),
+    #[cfg(not(feature = "serde_qs"))]
+    Source::QsQuery | Source::QsForm => None,
+    Source::Either => Some(TypeId::of::<EitherConfig>()),
+    Source::Body => Some(TypeId::of::<BodyConfig>()),
+    Source::Extractor => None,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::error::{Location, Source};
+  use crate::web::{ExtractorMeta, GardeConfig, Json, JsonConfig, Query, ValidationErrorHandler};
+  use actix_http::StatusCode;
+  use actix_web::error::InternalError;
+  use actix_web::test::{TestRequest, call_service, init_service, read_body};
+  use actix_web::web::{post, resource};
+  use actix_web::{App, Error, HttpRequest, HttpResponse};
+  use garde::Validate;
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Debug, PartialEq, Validate, Serialize, Deserialize)]
+  struct AgeData {
+    #[garde(range(min = 18, max = 28))]
+    age: u8,
+  }
+
+  struct DescribeBody;
+
+  impl ValidationErrorHandler for DescribeBody {
+    fn handle(
+      &self,
+      error: crate::error::Error,
+      _req: &HttpRequest,
+      meta: &ExtractorMeta<'_>,
+    ) -> Result<Error, crate::error::Error> {
+      match meta.location() {
+        Location::Body => {
+          let has_config = meta.extractor_config::<JsonConfig>().is_some();
+          let body = format!("{} {} {}", meta.source(), has_config, meta.type_name());
+          Ok(InternalError::from_response(error, HttpResponse::UnprocessableEntity().body(body)).into())
+        }
+        _ => Err(error),
+      }
+    }
+  }
+
+  async fn test_json_handler(_: Json<AgeData>) -> HttpResponse {
+    HttpResponse::Ok().finish()
+  }
+
+  async fn test_query_handler(_: Query<AgeData>) -> HttpResponse {
+    HttpResponse::Ok().finish()
+  }
+
+  #[tokio::test]
+  async fn test_handler_metadata() {
+    let app = init_service(
+      App::new()
+        .app_data(JsonConfig::default().handler(DescribeBody))
+        .service(resource("/").route(post().to(test_json_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post().uri("/").set_json(&AgeData { age: 30 }).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body = read_body(resp).await;
+    assert!(body.starts_with(Source::Json.as_str().as_bytes()));
+    assert!(body.windows(6).any(|w| w == b" true "));
+    assert!(body.ends_with(b"AgeData"));
+  }
+
+  #[tokio::test]
+  async fn test_handler_chain_fallback() {
+    let handler = DescribeBody.or_else(|err: crate::error::Error, _req: &HttpRequest| -> Error {
+      InternalError::from_response(err, HttpResponse::Conflict().finish()).into()
+    });
+    let app = init_service(
+      App::new()
+        .app_data(GardeConfig::default().handler(handler))
+        .service(resource("/json").route(post().to(test_json_handler)))
+        .service(resource("/query").route(post().to(test_query_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post()
+      .uri("/json")
+      .set_json(&AgeData { age: 30 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let req = TestRequest::post().uri("/query?age=30").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+  }
+
+  #[tokio::test]
+  async fn test_declined_error_uses_default_response() {
+    let app = init_service(
+      App::new()
+        .app_data(GardeConfig::default().handler(DescribeBody))
+        .service(resource("/").route(post().to(test_query_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post().uri("/?age=30").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+  }
+}
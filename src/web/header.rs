@@ -1,11 +1,17 @@
+use crate::context::ContextFallback;
+use crate::error::Source;
 use crate::validate_for_request;
+use crate::web::config::{ErrorHandler, handle_error};
+use crate::web::{GardeExtractor, ValidationErrorHandler};
 use actix_http::header::Header as ParseHeader;
 use actix_web::dev::Payload;
 use actix_web::error::Error;
+use actix_web::web::Data;
 use actix_web::{FromRequest, HttpRequest};
 use derive_more::{AsRef, Deref, DerefMut, Display, From};
-use futures::future::{Ready, err, ok};
+use futures::future::{Ready, err, ok, ready};
 use garde::Validate;
+use std::sync::Arc;
 
 /// Drop in replacement for [actix_web::web::Header](https://docs.rs/actix-web/latest/actix_web/web/struct.Header.html)
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deref, DerefMut, AsRef, Display, From)]
@@ -18,35 +24,116 @@ impl<T> Header<T> {
   }
 }
 
+impl_serialize!(Header);
+
 impl<T> FromRequest for Header<T>
 where
   T: ParseHeader + Validate + 'static,
-  T::Context: Default,
+  T::Context: ContextFallback,
 {
   type Error = Error;
   type Future = Ready<Result<Self, Self::Error>>;
 
   #[inline]
   fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
-    match ParseHeader::parse(req) {
-      Ok(header) => match validate_for_request(header, &req.clone()) {
-        Ok(header) => ok(Header(header)),
-        Err(e) => err(e.into()),
-      },
-      Err(e) => err(e.into()),
+    let res = ParseHeader::parse(req)
+      .map_err(|e| crate::error::Error::new(e, Source::Header))
+      .and_then(|header| validate_for_request(header, req, Source::Header));
+
+    match res {
+      Ok(header) => ok(Header(header)),
+      Err(e) => {
+        log::debug!(
+          "Failed during Header extractor validation. \
+                     Request path: {}",
+          req.path()
+        );
+
+        err(handle_error::<T>(e, req, Source::Header, Self::err_handler(req)))
+      }
     }
   }
 }
 
+impl<T> GardeExtractor for Header<T>
+where
+  T: ParseHeader + Validate + 'static,
+{
+  type Inner = T;
+  type InnerFuture = Ready<Result<T, crate::error::Error>>;
+
+  const SOURCE: Source = Source::Header;
+
+  fn extract_inner(req: &HttpRequest, _: &mut Payload) -> Self::InnerFuture {
+    ready(ParseHeader::parse(req).map_err(Into::into))
+  }
+
+  fn from_inner(inner: T) -> Self {
+    Header(inner)
+  }
+
+  fn into_value(self) -> T {
+    self.0
+  }
+
+  fn err_handler(req: &HttpRequest) -> Option<&dyn ValidationErrorHandler> {
+    Some(HeaderConfig::from_req(req).err_handler.as_deref()?)
+  }
+}
+
+/// Configuration of the [`Header`] extractor, the error handler receives parse and validation errors
+#[derive(Clone)]
+pub struct HeaderConfig {
+  pub(crate) err_handler: Option<ErrorHandler>,
+}
+
+impl HeaderConfig {
+  pub fn error_handler<F>(mut self, f: F) -> Self
+  where
+    F: Fn(crate::error::Error, &HttpRequest) -> Error + Send + Sync + 'static,
+  {
+    self.err_handler = Some(Arc::new(f));
+    self
+  }
+
+  /// Error handler receiving the extractor metadata, see [`ValidationErrorHandler`]
+  pub fn handler<H>(mut self, handler: H) -> Self
+  where
+    H: ValidationErrorHandler + Send + Sync + 'static,
+  {
+    self.err_handler = Some(Arc::new(handler));
+    self
+  }
+
+  pub fn from_req(req: &HttpRequest) -> &Self {
+    req
+      .app_data::<Self>()
+      .or_else(|| req.app_data::<Data<Self>>().map(Data::get_ref))
+      .unwrap_or(&DEFAULT_CONFIG)
+  }
+}
+
+const DEFAULT_CONFIG: HeaderConfig = HeaderConfig { err_handler: None };
+
+impl Default for HeaderConfig {
+  fn default() -> Self {
+    DEFAULT_CONFIG
+  }
+}
+
 #[cfg(test)]
 mod test {
-  use crate::web::Header;
+  use crate::error::ErrorKind;
+  use crate::web::{Header, HeaderConfig};
   use actix_http::error::ParseError;
   use actix_http::header::Header as ParseHeader;
   use actix_http::header::{HeaderName, HeaderValue, InvalidHeaderValue, TryIntoHeaderValue};
   use actix_http::{HttpMessage, StatusCode};
   use actix_test::TestRequest;
-  use actix_web::FromRequest;
+  use actix_web::error::InternalError;
+  use actix_web::test::{call_service, init_service};
+  use actix_web::web::{Data, get, resource};
+  use actix_web::{App, FromRequest, HttpResponse};
   use garde::Validate;
   use serde::{Deserialize, Serialize};
 
@@ -97,4 +184,65 @@ mod test {
     let res = Header::<HeaderData>::from_request(&req, &mut pl).await;
     assert!(res.is_ok());
   }
+
+  async fn test_handler(header: Header<HeaderData>) -> HttpResponse {
+    HttpResponse::Ok().body(header.age.to_string())
+  }
+
+  #[tokio::test]
+  async fn test_header_config() {
+    let app = init_service(
+      App::new()
+        .app_data(HeaderConfig::default().error_handler(|err, _req| {
+          let status = match err.kind() {
+            ErrorKind::Header => StatusCode::PRECONDITION_FAILED,
+            _ => StatusCode::UNPROCESSABLE_ENTITY,
+          };
+          InternalError::new(err, status).into()
+        }))
+        .service(resource("/").route(get().to(test_handler))),
+    )
+    .await;
+
+    let req = TestRequest::get()
+      .uri("/")
+      .insert_header(("header-data", "24"))
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = TestRequest::get()
+      .uri("/")
+      .insert_header(("header-data", "30"))
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let req = TestRequest::get()
+      .uri("/")
+      .insert_header(("header-data", "abc"))
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::PRECONDITION_FAILED);
+
+    let req = TestRequest::get().uri("/").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::PRECONDITION_FAILED);
+  }
+
+  #[tokio::test]
+  async fn test_header_config_data() {
+    let app = init_service(
+      App::new()
+        .app_data(Data::new(HeaderConfig::default().error_handler(|err, _req| {
+          InternalError::new(err, StatusCode::PRECONDITION_FAILED).into()
+        })))
+        .service(resource("/").route(get().to(test_handler))),
+    )
+    .await;
+
+    let req = TestRequest::get().uri("/").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::PRECONDITION_FAILED);
+  }
 }
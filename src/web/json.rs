@@ -1,15 +1,27 @@
-use crate::validate_for_request;
+use crate::context::ContextFallback;
+use crate::error::Source;
+use crate::limits::Limits;
+use crate::web::buffer::BufferedBody;
+use crate::web::config::{ErrorHandler, handle_error};
+use crate::web::{ExtractFut, GardeExtractor, ValidationErrorHandler};
+use actix_web::body::EitherBody;
 use actix_web::dev::{JsonBody, Payload};
-use actix_web::{Error, FromRequest, HttpRequest, web};
-use futures::FutureExt;
-use futures::future::LocalBoxFuture;
+use actix_web::error::JsonPayloadError;
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest, HttpResponse, Responder, web};
+use futures::future::{Either, ErrInto};
+use futures::{FutureExt, TryFutureExt, ready};
 use garde::Validate;
+use serde::Serialize;
 use serde::de::DeserializeOwned;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
 use std::{fmt, ops};
 
 /// Drop in replacement for [actix_web::web::Json](https://docs.rs/actix-web/latest/actix_web/web/struct.Json.html)
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Json<T>(pub T);
 
 impl<T> Json<T> {
@@ -38,65 +50,117 @@ impl<T: fmt::Display> fmt::Display for Json<T> {
   }
 }
 
+impl<T> From<T> for Json<T> {
+  fn from(inner: T) -> Self {
+    Json(inner)
+  }
+}
+
+impl_serialize!(Json);
+
+/// Serializes the value as `application/json`, serialization errors go through the `JsonConfig` error handler
+///
+/// Without handler, they are answered with a 500 as actix does.
+impl<T: Serialize> Responder for Json<T> {
+  type Body = EitherBody<String>;
+
+  fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+    match serde_json::to_string(&self.0) {
+      Ok(body) => match HttpResponse::Ok()
+        .content_type(mime::APPLICATION_JSON)
+        .message_body(body)
+      {
+        Ok(res) => res.map_into_left_body(),
+        Err(err) => HttpResponse::from_error(err).map_into_right_body(),
+      },
+      Err(err) => {
+        let err_handler = JsonConfig::from_req(req).err_handler.as_deref().map(|h| h as _);
+        let err = handle_error::<T>(JsonPayloadError::Serialize(err).into(), req, Source::Json, err_handler);
+        HttpResponse::from_error(err).map_into_right_body()
+      }
+    }
+  }
+}
+
 impl<T> FromRequest for Json<T>
 where
   T: DeserializeOwned + Validate + 'static,
-  T::Context: Default,
+  T::Context: ContextFallback,
 {
   type Error = Error;
-  type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+  type Future = ExtractFut<Self>;
 
   #[inline]
   fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
-    let req_copy = req.clone();
-    let req_copy2 = req.clone();
+    ExtractFut::new(req, payload)
+  }
+}
+
+impl<T> GardeExtractor for Json<T>
+where
+  T: DeserializeOwned + Validate + 'static,
+{
+  type Inner = T;
+  type InnerFuture = Either<ErrInto<JsonBody<T>, crate::error::Error>, JsonLimitedBody<T>>;
+
+  const SOURCE: Source = Source::Json;
 
+  fn extract_inner(req: &HttpRequest, payload: &mut Payload) -> Self::InnerFuture {
     let config = JsonConfig::from_req(req);
 
-    let limit = config.limit;
-    let ctype_required = config.content_type_required;
-    let ctype_fn = config.content_type.as_deref();
-    let err_handler = config.err_handler.clone();
-
-    JsonBody::new(req, payload, ctype_fn, ctype_required)
-      .limit(limit)
-      .map(move |res: Result<T, _>| match res {
-        Ok(data) => {
-          let req = req_copy;
-          validate_for_request(data, &req)
-        }
-        Err(e) => Err(e.into()),
+    if config.backend == JsonBackend::SerdeJson && config.limits == Limits::NONE {
+      Either::Left(json_body(req, payload))
+    } else {
+      Either::Right(JsonLimitedBody {
+        body: buffer_json_body(req, payload),
+        backend: config.backend,
+        limits: config.limits,
+        data: PhantomData,
       })
-      .map(move |res| match res {
-        Err(err) => {
-          log::debug!(
-            "Failed to deserialize Json from payload. \
-                         Request path: {}",
-            req_copy2.path()
-          );
-
-          if let Some(err_handler) = err_handler.as_ref() {
-            Err((*err_handler)(err, &req_copy2))
-          } else {
-            Err(err.into())
-          }
-        }
-        Ok(data) => Ok(Json(data)),
-      })
-      .boxed_local()
+    }
+  }
+
+  fn from_inner(inner: T) -> Self {
+    Json(inner)
+  }
+
+  fn into_value(self) -> T {
+    self.0
   }
-}
 
-type JsonErrorHandler = Option<Arc<dyn Fn(crate::error::Error, &HttpRequest) -> Error + Send + Sync>>;
+  fn err_handler(req: &HttpRequest) -> Option<&dyn ValidationErrorHandler> {
+    Some(JsonConfig::from_req(req).err_handler.as_deref()?)
+  }
+}
 
 /// Replacement for [actix_web::web::JsonConfig](https://docs.rs/actix-web/latest/actix_web/web/struct.JsonConfig.html)
 /// Error handler must map from an `garde_actix_web::error::Error`
 #[derive(Clone)]
 pub struct JsonConfig {
-  limit: usize,
-  err_handler: JsonErrorHandler,
-  content_type: Option<Arc<dyn Fn(mime::Mime) -> bool + Send + Sync>>,
-  content_type_required: bool,
+  pub(crate) limit: usize,
+  pub(crate) err_handler: Option<ErrorHandler>,
+  pub(crate) content_type: Option<Arc<dyn Fn(mime::Mime) -> bool + Send + Sync>>,
+  pub(crate) content_type_required: bool,
+  pub(crate) limits: Limits,
+  backend: JsonBackend,
+}
+
+/// Parser deserializing [`Json`] bodies, see [`JsonConfig::backend`]
+///
+/// [`JsonBytes`](crate::web::JsonBytes) always uses `serde_json`, `simd-json` modifying the buffer it parses.
+///
+/// Both backends report failures as `JsonPayloadError::Deserialize`, holding a `serde_json::Error` of the same category.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum JsonBackend {
+  /// `serde_json`, the default
+  SerdeJson,
+  /// `simd-json`, available with the `simd_json` feature
+  ///
+  /// Syntax errors are reported by parsing a copy of the body with `serde_json`, the body being modified in place
+  /// by `simd-json`. Data errors keep the `simd-json` message.
+  #[cfg(feature = "simd_json")]
+  SimdJson,
 }
 
 impl JsonConfig {
@@ -113,6 +177,15 @@ impl JsonConfig {
     self
   }
 
+  /// Error handler receiving the extractor metadata, see [`ValidationErrorHandler`]
+  pub fn handler<H>(mut self, handler: H) -> Self
+  where
+    H: ValidationErrorHandler + Send + Sync + 'static,
+  {
+    self.err_handler = Some(Arc::new(handler));
+    self
+  }
+
   pub fn content_type<F>(mut self, predicate: F) -> Self
   where
     F: Fn(mime::Mime) -> bool + Send + Sync + 'static,
@@ -126,6 +199,18 @@ impl JsonConfig {
     self
   }
 
+  /// Parser used to deserialize the body, `serde_json` by default, see [`JsonBackend`]
+  pub fn backend(mut self, backend: JsonBackend) -> Self {
+    self.backend = backend;
+    self
+  }
+
+  /// Structural limits checked while deserializing the body, see [`Limits`]
+  pub fn limits(mut self, limits: Limits) -> Self {
+    self.limits = limits;
+    self
+  }
+
   pub fn from_req(req: &HttpRequest) -> &Self {
     req
       .app_data::<Self>()
@@ -141,6 +226,8 @@ const DEFAULT_CONFIG: JsonConfig = JsonConfig {
   err_handler: None,
   content_type: None,
   content_type_required: true,
+  limits: Limits::NONE,
+  backend: JsonBackend::SerdeJson,
 };
 
 impl Default for JsonConfig {
@@ -149,16 +236,107 @@ impl Default for JsonConfig {
   }
 }
 
+/// Buffers the body following the `JsonConfig` content type and limit, as `JsonBody` does
+pub(crate) fn buffer_json_body(req: &HttpRequest, payload: &mut Payload) -> BufferedBody {
+  let config = JsonConfig::from_req(req);
+
+  let can_parse_json = match req.mime_type() {
+    Ok(Some(mime)) => accepts_json(config, mime),
+    _ => !config.content_type_required,
+  };
+
+  BufferedBody::new(req, payload, Source::Json, can_parse_json, config.limit)
+}
+
+/// Whether `Json` reads bodies of type `mime`: JSON and `+json` types, or the ones accepted by the config predicate
+pub(crate) fn accepts_json(config: &JsonConfig, mime: mime::Mime) -> bool {
+  mime.subtype() == mime::JSON
+    || mime.suffix() == Some(mime::JSON)
+    || config.content_type.as_deref().is_some_and(|predicate| predicate(mime))
+}
+
+fn json_body<T: DeserializeOwned>(
+  req: &HttpRequest,
+  payload: &mut Payload,
+) -> ErrInto<JsonBody<T>, crate::error::Error> {
+  let config = JsonConfig::from_req(req);
+
+  let limit = config.limit;
+  let ctype_required = config.content_type_required;
+  let ctype_fn = config.content_type.as_deref();
+
+  JsonBody::new(req, payload, ctype_fn, ctype_required)
+    .limit(limit)
+    .err_into()
+}
+
+/// Buffers a [`Json`] body, then deserializes it with the `JsonConfig` backend, checking its limits
+pub struct JsonLimitedBody<T> {
+  body: BufferedBody,
+  backend: JsonBackend,
+  limits: Limits,
+  data: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> Future for JsonLimitedBody<T> {
+  type Output = Result<T, crate::error::Error>;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let this = self.get_mut();
+    let mut body = ready!(this.body.poll_unpin(cx))?;
+
+    Poll::Ready(deserialize_json_body(this.backend, this.limits, &mut body))
+  }
+}
+
+/// Deserializes the body with the configured backend, `simd-json` parsing it in place
+fn deserialize_json_body<T: DeserializeOwned>(
+  backend: JsonBackend,
+  limits: Limits,
+  body: &mut [u8],
+) -> Result<T, crate::error::Error> {
+  match backend {
+    JsonBackend::SerdeJson => {
+      let mut deserializer = serde_json::Deserializer::from_slice(body);
+      let data = limits.deserialize(&mut deserializer, |e| JsonPayloadError::Deserialize(e).into())?;
+      deserializer.end().map_err(JsonPayloadError::Deserialize)?;
+      Ok(data)
+    }
+    #[cfg(feature = "simd_json")]
+    JsonBackend::SimdJson => {
+      let input = body.to_vec();
+      let mut deserializer = simd_json::Deserializer::from_slice(body).map_err(|e| simd_json_error(e, &input))?;
+      limits.deserialize(&mut deserializer, |e| simd_json_error(e, &input).into())
+    }
+  }
+}
+
+/// Reports a `simd-json` failure as the `JsonPayloadError::Deserialize` of the same category `serde_json` raises
+#[cfg(feature = "simd_json")]
+fn simd_json_error(err: simd_json::Error, input: &[u8]) -> JsonPayloadError {
+  if !err.is_data() {
+    if let Err(e) = serde_json::from_slice::<serde::de::IgnoredAny>(input) {
+      return JsonPayloadError::Deserialize(e);
+    }
+  }
+
+  JsonPayloadError::Deserialize(serde::de::Error::custom(err))
+}
+
 #[cfg(test)]
 mod test {
-  use crate::web::{Json, JsonConfig};
+  use crate::error::{ErrorCause, ErrorFormat};
+  use crate::limits::Limits;
+  use crate::web::{Json, JsonBackend, JsonConfig};
   use actix_http::StatusCode;
-  use actix_web::error::InternalError;
-  use actix_web::test::{TestRequest, call_service, init_service};
-  use actix_web::web::{post, resource};
+  use actix_http::header::CONTENT_TYPE;
+  use actix_web::error::{InternalError, JsonPayloadError};
+  use actix_web::test::{TestRequest, call_service, init_service, read_body, read_body_json};
+  use actix_web::web::{get, post, resource};
   use actix_web::{App, HttpResponse};
   use garde::Validate;
   use serde::{Deserialize, Serialize};
+  use std::collections::HashMap;
 
   #[derive(Debug, PartialEq, Validate, Serialize, Deserialize)]
   struct JsonData {
@@ -193,6 +371,119 @@ mod test {
     HttpResponse::Ok().finish()
   }
 
+  #[tokio::test]
+  async fn test_json_backends() {
+    assert_eq!(JsonConfig::default().backend, JsonBackend::SerdeJson);
+
+    #[allow(unused_mut)]
+    let mut backends = vec![JsonBackend::SerdeJson];
+    #[cfg(feature = "simd_json")]
+    backends.push(JsonBackend::SimdJson);
+
+    for backend in backends {
+      let app = init_service(
+        App::new()
+          .app_data(JsonConfig::default().backend(backend).limit(32))
+          .service(resource("/").route(post().to(test_handler))),
+      )
+      .await;
+
+      let req = TestRequest::post()
+        .uri("/")
+        .set_json(&JsonData { age: 24 })
+        .to_request();
+      let resp = call_service(&app, req).await;
+      assert_eq!(resp.status(), StatusCode::OK);
+
+      let req = TestRequest::post()
+        .uri("/")
+        .set_json(&JsonData { age: 30 })
+        .to_request();
+      let resp = call_service(&app, req).await;
+      assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+      let req = TestRequest::post()
+        .uri("/")
+        .insert_header((CONTENT_TYPE, "application/json"))
+        .set_payload("{\"age\": \"abc\"}")
+        .to_request();
+      let resp = call_service(&app, req).await;
+      assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+      let req = TestRequest::post()
+        .uri("/")
+        .insert_header((CONTENT_TYPE, "text/plain"))
+        .set_payload("{\"age\": 24}")
+        .to_request();
+      let resp = call_service(&app, req).await;
+      assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+      let req = TestRequest::post()
+        .uri("/")
+        .insert_header((CONTENT_TYPE, "application/json"))
+        .set_payload(format!("{{\"age\": 24, \"name\": \"{}\"}}", "a".repeat(32)))
+        .to_request();
+      let resp = call_service(&app, req).await;
+      assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+  }
+
+  fn error_class(err: &crate::error::Error) -> &'static str {
+    match err.as_cause() {
+      ErrorCause::JsonPayloadError(JsonPayloadError::Deserialize(e)) if e.is_syntax() => "syntax",
+      ErrorCause::JsonPayloadError(JsonPayloadError::Deserialize(e)) if e.is_data() => "data",
+      ErrorCause::JsonPayloadError(JsonPayloadError::Deserialize(e)) if e.is_eof() => "eof",
+      _ => "other",
+    }
+  }
+
+  #[tokio::test]
+  async fn test_json_backend_error_classification() {
+    #[allow(unused_mut)]
+    let mut backends = vec![JsonBackend::SerdeJson];
+    #[cfg(feature = "simd_json")]
+    backends.push(JsonBackend::SimdJson);
+
+    for backend in backends {
+      let app = init_service(
+        App::new()
+          .app_data(JsonConfig::default().backend(backend).error_handler(|err, _req| {
+            let class = error_class(&err);
+            InternalError::from_response(err, HttpResponse::BadRequest().body(class)).into()
+          }))
+          .service(resource("/").route(post().to(test_handler))),
+      )
+      .await;
+
+      let req = TestRequest::post()
+        .uri("/")
+        .insert_header((CONTENT_TYPE, "application/json"))
+        .set_payload("{\"age\" 24}")
+        .to_request();
+      let resp = call_service(&app, req).await;
+      assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+      assert_eq!(read_body(resp).await, "syntax", "{backend:?}");
+
+      let req = TestRequest::post()
+        .uri("/")
+        .insert_header((CONTENT_TYPE, "application/json"))
+        .set_payload("{\"age\": 24")
+        .to_request();
+      let resp = call_service(&app, req).await;
+      assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+      assert_eq!(read_body(resp).await, "eof", "{backend:?}");
+
+      let req = TestRequest::post()
+        .uri("/")
+        .insert_header((CONTENT_TYPE, "application/json"))
+        .set_payload("{\"age\": \"abc\"}")
+        .to_request();
+      let resp = call_service(&app, req).await;
+      assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+      assert_eq!(read_body(resp).await, "data", "{backend:?}");
+    }
+  }
+
   #[tokio::test]
   async fn test_simple_json_validation() {
     let app = init_service(App::new().service(resource("/").route(post().to(test_handler)))).await;
@@ -239,6 +530,29 @@ mod test {
     assert_eq!(resp.status(), StatusCode::CONFLICT);
   }
 
+  #[tokio::test]
+  async fn test_json_validation_json_error_format() {
+    let app = init_service(
+      App::new()
+        .app_data(ErrorFormat::Json)
+        .service(resource("/").route(post().to(test_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post()
+      .uri("/")
+      .set_json(&JsonData { age: 30 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(resp.headers().get(CONTENT_TYPE).unwrap(), "application/json");
+
+    let body: serde_json::Value = read_body_json(resp).await;
+    assert_eq!(body["errors"][0]["path"], "age");
+    assert_eq!(body["errors"][0]["source"], "json");
+    assert!(body["errors"][0]["message"].is_string());
+  }
+
   #[tokio::test]
   async fn test_json_validation_with_context() {
     let number_context = NumberContext { min: 25 };
@@ -282,4 +596,108 @@ mod test {
     let resp = call_service(&app, req).await;
     assert_eq!(resp.status(), StatusCode::OK);
   }
+
+  async fn test_echo_handler(json: Json<JsonData>) -> Json<JsonData> {
+    json
+  }
+
+  async fn test_unserializable_handler() -> Json<HashMap<(u8, u8), u8>> {
+    Json(HashMap::from([((1, 2), 3)]))
+  }
+
+  #[tokio::test]
+  async fn test_json_responder() {
+    let app = init_service(
+      App::new()
+        .service(resource("/").route(post().to(test_echo_handler)))
+        .service(resource("/unserializable").route(get().to(test_unserializable_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post()
+      .uri("/")
+      .set_json(&JsonData { age: 24 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+      resp.headers().get(CONTENT_TYPE).and_then(|h| h.to_str().ok()),
+      Some("application/json")
+    );
+    assert_eq!(read_body(resp).await, "{\"age\":24}");
+
+    let req = TestRequest::get().uri("/unserializable").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    let app = init_service(
+      App::new()
+        .app_data(
+          JsonConfig::default()
+            .error_handler(|err, _req| InternalError::from_response(err, HttpResponse::Conflict().finish()).into()),
+        )
+        .service(resource("/unserializable").route(get().to(test_unserializable_handler))),
+    )
+    .await;
+
+    let req = TestRequest::get().uri("/unserializable").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+  }
+
+  #[tokio::test]
+  async fn test_json_limits() {
+    #[allow(unused_mut)]
+    let mut backends = vec![JsonBackend::SerdeJson];
+    #[cfg(feature = "simd_json")]
+    backends.push(JsonBackend::SimdJson);
+
+    for backend in backends {
+      let limits = Limits::default()
+        .max_depth(2)
+        .max_string_length(8)
+        .reject_duplicate_keys(true);
+      let app = init_service(
+        App::new()
+          .app_data(
+            JsonConfig::default()
+              .backend(backend)
+              .limits(limits)
+              .error_handler(|err, _req| {
+                let kind = format!("{:?}", err.kind());
+                InternalError::from_response(err, HttpResponse::BadRequest().body(kind)).into()
+              }),
+          )
+          .service(resource("/").route(post().to(test_handler))),
+      )
+      .await;
+
+      let cases = [
+        ("{\"age\": 24, \"tags\": [\"a\"]}", StatusCode::OK, ""),
+        ("{\"age\": 30}", StatusCode::BAD_REQUEST, "Validation"),
+        (
+          "{\"age\": 24, \"tags\": [[\"a\"]]}",
+          StatusCode::BAD_REQUEST,
+          "DepthLimit",
+        ),
+        (
+          "{\"age\": 24, \"name\": \"netwo netwo\"}",
+          StatusCode::BAD_REQUEST,
+          "StringLengthLimit",
+        ),
+        ("{\"age\": 24, \"age\": 25}", StatusCode::BAD_REQUEST, "DuplicateKey"),
+        ("{\"age\": 24} 1", StatusCode::BAD_REQUEST, "JsonPayload"),
+      ];
+      for (payload, status, kind) in cases {
+        let req = TestRequest::post()
+          .uri("/")
+          .insert_header((CONTENT_TYPE, "application/json"))
+          .set_payload(payload)
+          .to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), status, "{payload}");
+        assert_eq!(read_body(resp).await, kind, "{payload}");
+      }
+    }
+  }
 }
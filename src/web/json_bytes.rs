@@ -0,0 +1,281 @@
+use crate::context::ContextFallback;
+use crate::error::Source;
+use crate::web::borrowed::validate_borrowed;
+use crate::web::config::handle_error;
+use crate::web::json::buffer_json_body;
+use crate::web::{Borrowed, JsonConfig};
+use actix_web::dev::Payload;
+use actix_web::error::JsonPayloadError;
+use actix_web::web::Bytes;
+use actix_web::{Error, FromRequest, HttpRequest};
+use futures::FutureExt;
+use futures::future::LocalBoxFuture;
+use serde::Serialize;
+use std::fmt;
+use std::rc::Rc;
+use yoke::{Yoke, Yokeable};
+
+/// JSON extractor keeping the buffered body, and validating `T::Data` borrowing from it
+///
+/// Strings of large payloads are not copied: the data is deserialized and validated once, borrowing from the kept bytes,
+/// which are also available to the handler. The limits, content type and error handler of [`JsonConfig`] apply.
+/// [`AsyncValidate`](crate::async_validate::AsyncValidate) hooks are not run, the borrowed data not being `'static`.
+///
+/// ```rust
+/// use actix_web::HttpResponse;
+/// use garde::Validate;
+/// use garde_actix_web::web::JsonBytes;
+/// use serde::Deserialize;
+/// use std::borrow::Cow;
+/// use yoke::Yokeable;
+///
+/// #[derive(Deserialize, Validate, Yokeable)]
+/// struct Event<'a> {
+///   // borrowed from the body, unless the JSON string holds escape sequences
+///   #[garde(length(min = 1))]
+///   #[serde(borrow)]
+///   name: Cow<'a, str>,
+/// }
+///
+/// async fn ingest(event: JsonBytes<Event<'static>>) -> HttpResponse {
+///   HttpResponse::Accepted().body(format!("{} ({} bytes)", event.get().name, event.bytes().len()))
+/// }
+/// ```
+pub struct JsonBytes<T: Borrowed> {
+  data: Yoke<T, Rc<Bytes>>,
+}
+
+impl<T: Borrowed> JsonBytes<T> {
+  /// Buffered body, decompressed according to its `Content-Encoding`
+  pub fn bytes(&self) -> &Bytes {
+    self.data.backing_cart()
+  }
+
+  /// Unwraps the buffered body, decompressed according to its `Content-Encoding`
+  pub fn into_bytes(self) -> Bytes {
+    Rc::unwrap_or_clone(self.data.into_backing_cart())
+  }
+
+  /// Borrowed data, validated during extraction
+  pub fn get(&self) -> &<T as Yokeable<'_>>::Output {
+    self.data.get()
+  }
+
+  /// Converts the data into an owned type, for values outliving the request
+  pub fn into_owned<O>(self) -> O
+  where
+    O: for<'a> From<&'a <T as Yokeable<'a>>::Output>,
+  {
+    O::from(self.data.get())
+  }
+}
+
+impl<T: Borrowed> fmt::Debug for JsonBytes<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("JsonBytes").field("bytes", self.bytes()).finish()
+  }
+}
+
+impl<T: Borrowed> Clone for JsonBytes<T>
+where
+  for<'a> <T as Yokeable<'a>>::Output: Clone,
+{
+  fn clone(&self) -> Self {
+    JsonBytes {
+      data: self.data.clone(),
+    }
+  }
+}
+
+impl<T: Borrowed> From<JsonBytes<T>> for Bytes {
+  fn from(json: JsonBytes<T>) -> Self {
+    json.into_bytes()
+  }
+}
+
+impl<T: Borrowed> fmt::Display for JsonBytes<T>
+where
+  for<'a> <T as Yokeable<'a>>::Output: fmt::Display,
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt::Display::fmt(self.get(), f)
+  }
+}
+
+impl<T: Borrowed> Serialize for JsonBytes<T>
+where
+  for<'a> <T as Yokeable<'a>>::Output: Serialize,
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: serde::Serializer,
+  {
+    self.get().serialize(serializer)
+  }
+}
+
+impl<T> FromRequest for JsonBytes<T>
+where
+  T: Borrowed,
+  T::Context: ContextFallback,
+{
+  type Error = Error;
+  type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+  #[inline]
+  fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+    let req = req.clone();
+    let body = buffer_json_body(&req, payload);
+
+    async move {
+      let res = async {
+        let bytes = body.await.map_err(|e| e.with_origin(Source::Json))?.freeze();
+        let limits = JsonConfig::from_req(&req).limits;
+
+        let data = Yoke::try_attach_to_cart(Rc::new(bytes), |bytes| {
+          let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+          let data: <T as Yokeable<'_>>::Output =
+            limits.deserialize(&mut deserializer, |e| JsonPayloadError::Deserialize(e).into())?;
+          deserializer.end().map_err(JsonPayloadError::Deserialize)?;
+          validate_borrowed::<T>(&data, &req, Source::Json).map(|()| data)
+        })?;
+
+        Ok(JsonBytes { data })
+      };
+
+      res.await.map_err(|e| {
+        log::debug!(
+          "Failed to deserialize Json from payload. \
+                         Request path: {}",
+          req.path()
+        );
+
+        let err_handler = JsonConfig::from_req(&req).err_handler.as_deref().map(|h| h as _);
+        handle_error::<T>(e, &req, Source::Json, err_handler)
+      })
+    }
+    .boxed_local()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::web::{JsonBytes, JsonConfig};
+  use actix_http::StatusCode;
+  use actix_http::header::{CONTENT_ENCODING, CONTENT_TYPE};
+  use actix_web::error::InternalError;
+  use actix_web::test::{TestRequest, call_service, init_service, read_body};
+  use actix_web::web::{post, resource};
+  use actix_web::{App, HttpResponse};
+  use flate2::Compression;
+  use flate2::write::GzEncoder;
+  use garde::Validate;
+  use serde::Deserialize;
+  use std::borrow::Cow;
+  use std::io::Write;
+  use yoke::Yokeable;
+
+  #[derive(Debug, Validate, Deserialize, Yokeable)]
+  struct EventData<'a> {
+    #[garde(length(min = 1, max = 10))]
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+  }
+
+  async fn test_handler(event: JsonBytes<EventData<'static>>) -> HttpResponse {
+    let data = event.get();
+    let borrowed = matches!(data.name, Cow::Borrowed(_));
+    HttpResponse::Ok().body(format!("{} {} {}", data.name, borrowed, event.bytes().len()))
+  }
+
+  #[tokio::test]
+  async fn test_json_bytes() {
+    let app = init_service(App::new().service(resource("/").route(post().to(test_handler)))).await;
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "application/json"))
+      .set_payload("{\"name\": \"netwo\"}")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(read_body(resp).await, "netwo true 17");
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "application/json"))
+      .set_payload("{\"name\": \"\"}")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "application/json"))
+      .set_payload("{\"name\": \"net\\\"wo\"}")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(read_body(resp).await, "net\"wo false 19");
+
+    let req = TestRequest::post()
+      .uri("/")
+      .set_payload("{\"name\": \"netwo\"}")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+  }
+
+  #[tokio::test]
+  async fn test_json_bytes_compressed() {
+    let app = init_service(App::new().service(resource("/").route(post().to(test_handler)))).await;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(b"{\"name\": \"netwo\"}").unwrap();
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "application/json"))
+      .insert_header((CONTENT_ENCODING, "gzip"))
+      .set_payload(encoder.finish().unwrap())
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(read_body(resp).await, "netwo true 17");
+  }
+
+  #[tokio::test]
+  async fn test_json_bytes_config() {
+    let app = init_service(
+      App::new()
+        .app_data(
+          JsonConfig::default()
+            .limit(16)
+            .content_type_required(false)
+            .error_handler(|err, _req| InternalError::new(err, StatusCode::CONFLICT).into()),
+        )
+        .service(resource("/").route(post().to(test_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post()
+      .uri("/")
+      .set_payload("{\"name\": \"ne\"}")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = TestRequest::post()
+      .uri("/")
+      .set_payload("{\"name\": \"netwo\"}")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    let req = TestRequest::post()
+      .uri("/")
+      .set_payload("{\"name\": \"\"}")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+  }
+}
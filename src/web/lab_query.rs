@@ -1,16 +1,19 @@
-use crate::validate_for_request;
-use crate::web::QueryConfig;
+use crate::context::ContextFallback;
+use crate::error::Source;
+use crate::web::ValidationErrorHandler;
+use crate::web::{ExtractFut, GardeExtractor, QueryConfig};
 use actix_web::dev::Payload;
 use actix_web::error::QueryPayloadError;
 use actix_web::{Error, FromRequest, HttpRequest};
-use actix_web_lab::__reexports::futures_util::future::LocalBoxFuture;
 use actix_web_lab::extract::QueryDeserializeError;
+use derive_more::{AsRef, Deref, DerefMut, Display, From};
+use futures::future::{Ready, ready};
 use garde::Validate;
 use serde::de;
 use serde::de::DeserializeOwned;
 
 /// Drop in replacement for [actix_web_lab::extract::Query](https://docs.rs/actix-web-lab/latest/actix_web_lab/extract/struct.Query.html)
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Deref, DerefMut, AsRef, Display, From)]
 pub struct Query<T>(pub T);
 
 impl<T> Query<T> {
@@ -19,6 +22,8 @@ impl<T> Query<T> {
   }
 }
 
+impl_serialize!(Query);
+
 impl<T: DeserializeOwned> Query<T> {
   pub fn from_query(query_str: &str) -> Result<Self, QueryDeserializeError> {
     actix_web_lab::extract::Query::from_query(query_str).map(|r: actix_web_lab::extract::Query<T>| Self(r.into_inner()))
@@ -28,42 +33,44 @@ impl<T: DeserializeOwned> Query<T> {
 impl<T> FromRequest for Query<T>
 where
   T: DeserializeOwned + Validate + 'static,
-  T::Context: Default,
+  T::Context: ContextFallback,
 {
   type Error = Error;
-  type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+  type Future = ExtractFut<Self>;
 
   #[inline]
   fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
-    let req_copy = req.clone();
-    let req = req.clone();
-    let mut payload = payload.take();
-
-    let error_handler = req.app_data::<QueryConfig>().and_then(|c| c.err_handler.clone());
-
-    Box::pin(async move {
-      actix_web_lab::extract::Query::from_request(&req, &mut payload)
-        .await
-        .map_err(|e| QueryPayloadError::Deserialize(de::Error::custom(format!("{}", e))).into())
-        .and_then(|data| {
-          let req = req_copy;
-          validate_for_request(data.0, &req)
-        })
-        .map(|res| Self(res))
-        .map_err(move |e| {
-          log::debug!(
-            "Failed during Query extractor deserialization. \
-                     Request path: {:?}",
-            req.path()
-          );
-
-          if let Some(error_handler) = error_handler {
-            (error_handler)(e, &req)
-          } else {
-            e.into()
-          }
-        })
-    })
+    ExtractFut::new(req, payload)
+  }
+}
+
+impl<T> GardeExtractor for Query<T>
+where
+  T: DeserializeOwned + Validate + 'static,
+{
+  type Inner = T;
+  type InnerFuture = Ready<Result<T, crate::error::Error>>;
+
+  const SOURCE: Source = Source::LabQuery;
+
+  fn extract_inner(req: &HttpRequest, _: &mut Payload) -> Self::InnerFuture {
+    ready(
+      Self::from_query(req.query_string())
+        .map(Self::into_inner)
+        .map_err(|e| QueryPayloadError::Deserialize(de::Error::custom(format!("{}", e))).into()),
+    )
+  }
+
+  fn from_inner(inner: T) -> Self {
+    Self(inner)
+  }
+
+  fn into_value(self) -> T {
+    self.0
+  }
+
+  fn err_handler(req: &HttpRequest) -> Option<&dyn ValidationErrorHandler> {
+    Some(req.app_data::<QueryConfig>()?.err_handler.as_deref()?)
   }
 }
 
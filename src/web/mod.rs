@@ -1,22 +1,59 @@
 //! Drop in types for actix web implementing garde
+
+/// Implements `Serialize` on wrappers by serializing the wrapped value
+macro_rules! impl_serialize {
+  ($wrapper:ident) => {
+    impl<T: serde::Serialize> serde::Serialize for $wrapper<T> {
+      fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+      where
+        S: serde::Serializer,
+      {
+        self.0.serialize(serializer)
+      }
+    }
+  };
+}
+
+mod body;
+mod borrowed;
+mod buffer;
+mod checked;
+mod config;
 mod either;
+mod extract;
 mod form;
+mod handler;
 mod header;
 mod json;
+mod json_bytes;
 #[cfg(feature = "lab_query")]
 mod lab_query;
 mod path;
 #[cfg(feature = "serde_qs")]
 mod qs;
 mod query;
+mod responder;
+mod valid;
+mod validated;
 
-pub use either::Either;
+pub use body::{Body, BodyConfig};
+pub use borrowed::{Borrowed, PathRef, QueryRef};
+pub use checked::Checked;
+pub use config::GardeConfig;
+#[allow(deprecated)]
+pub use either::{Either, Either3, Either4, EitherBranches, EitherConfig, EitherExtractError};
+pub use extract::{ExtractFut, GardeExtractor};
 pub use form::{Form, FormConfig};
-pub use header::Header;
-pub use json::{Json, JsonConfig};
+pub use handler::{Chain, ExtractorMeta, ValidationErrorHandler};
+pub use header::{Header, HeaderConfig};
+pub use json::{Json, JsonBackend, JsonConfig};
+pub use json_bytes::JsonBytes;
 #[cfg(feature = "lab_query")]
 pub use lab_query::Query as LabQuery;
 pub use path::{Path, PathConfig};
 #[cfg(feature = "serde_qs")]
 pub use qs::{QsForm, QsQuery, QsQueryConfig};
 pub use query::{Query, QueryConfig};
+pub use responder::{ResponseValidation, ValidatedJson};
+pub use valid::Valid;
+pub use validated::Validated;
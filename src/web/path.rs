@@ -1,19 +1,24 @@
 use actix_router::PathDeserializer;
 use actix_web::dev::Payload;
-use actix_web::error::{ErrorNotFound, PathError};
+use actix_web::error::PathError;
+use actix_web::http::StatusCode;
 use actix_web::web::Data;
 use actix_web::{Error, FromRequest, HttpRequest};
 use std::sync::Arc;
 
+use crate::context::ContextFallback;
+use crate::error::{ErrorCause, Source};
 use crate::validate_for_request;
+use crate::web::config::{ErrorHandler, handle_error};
+use crate::web::{GardeExtractor, ValidationErrorHandler};
 use derive_more::{AsRef, Deref, DerefMut, Display, From};
-use futures::future::{Ready, err, ok};
+use futures::future::{Ready, err, ok, ready};
 use garde::Validate;
 use serde::Deserialize;
 use serde::de::DeserializeOwned;
 
 /// Drop in replacement for [actix_web::web::Path](https://docs.rs/actix-web/latest/actix_web/web/struct.Path.html)
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Deref, DerefMut, AsRef, Display, From)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Deref, DerefMut, AsRef, Display, From)]
 pub struct Path<T>(T);
 
 impl<T> Path<T> {
@@ -22,30 +27,27 @@ impl<T> Path<T> {
   }
 }
 
+impl_serialize!(Path);
+
 impl<T> FromRequest for Path<T>
 where
   T: DeserializeOwned + Validate + 'static,
-  T::Context: Default,
+  T::Context: ContextFallback,
 {
   type Error = Error;
   type Future = Ready<Result<Self, Self::Error>>;
 
   #[inline]
-  fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+  fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
     let req_copy = req.clone();
-    let error_handler = req
-      .app_data::<PathConfig>()
-      .or_else(|| req.app_data::<Data<PathConfig>>().map(Data::get_ref))
-      .and_then(|c| c.err_handler.clone());
+    let error_handler = Self::err_handler(req);
 
-    Deserialize::deserialize(PathDeserializer::new(req.match_info()))
-      .map_err(|e| {
-        let e = PathError::Deserialize(e);
-        crate::error::Error::PathError(e)
-      })
+    Self::extract_inner(req, payload)
+      .into_inner()
+      .map_err(|e| e.with_origin(Source::Path))
       .and_then(|data: T| {
         let req = req_copy;
-        validate_for_request(data, &req)
+        validate_for_request(data, &req, Source::Path)
       })
       .map(|val| ok(Path(val)))
       .unwrap_or_else(move |e| {
@@ -55,23 +57,53 @@ where
           req.path()
         );
 
-        let e = if let Some(error_handler) = error_handler {
-          (error_handler)(e, req)
-        } else {
-          ErrorNotFound(e)
-        };
-
-        err(e)
+        err(handle_error::<T>(e, req, Source::Path, error_handler))
       })
   }
 }
 
+impl<T> GardeExtractor for Path<T>
+where
+  T: DeserializeOwned + Validate + 'static,
+{
+  type Inner = T;
+  type InnerFuture = Ready<Result<T, crate::error::Error>>;
+
+  const SOURCE: Source = Source::Path;
+
+  fn extract_inner(req: &HttpRequest, _: &mut Payload) -> Self::InnerFuture {
+    ready(
+      Deserialize::deserialize(PathDeserializer::new(req.match_info())).map_err(|e| {
+        let e = PathError::Deserialize(e);
+        crate::error::Error::from(e)
+      }),
+    )
+  }
+
+  fn from_inner(inner: T) -> Self {
+    Path(inner)
+  }
+
+  fn into_value(self) -> T {
+    self.0
+  }
+
+  fn err_handler(req: &HttpRequest) -> Option<&dyn ValidationErrorHandler> {
+    Some(PathConfig::from_req(req).err_handler.as_deref()?)
+  }
+}
+
 /// Replacement for [actix_web::web::PathConfig](https://docs.rs/actix-web/latest/actix_web/web/struct.PathConfig.html)
 /// Error handler must map from an `garde_actix_web::error::Error`
-#[derive(Clone, Default)]
+///
+/// Without error handler, both segments which cannot be deserialized and validation errors are answered with a 404.
+/// Use `validation_status(StatusCode::UNPROCESSABLE_ENTITY)` to tell rule violations apart from unmatched segments.
+/// These statuses take precedence over the ones set on [`GardeConfig`](crate::web::GardeConfig).
+#[derive(Clone)]
 pub struct PathConfig {
-  #[allow(clippy::type_complexity)]
-  err_handler: Option<Arc<dyn Fn(crate::error::Error, &HttpRequest) -> Error + Send + Sync>>,
+  pub(crate) err_handler: Option<ErrorHandler>,
+  validation_status: Option<StatusCode>,
+  deserialization_status: Option<StatusCode>,
 }
 
 impl PathConfig {
@@ -82,11 +114,59 @@ impl PathConfig {
     self.err_handler = Some(Arc::new(f));
     self
   }
+
+  /// Error handler receiving the extractor metadata, see [`ValidationErrorHandler`]
+  pub fn handler<H>(mut self, handler: H) -> Self
+  where
+    H: ValidationErrorHandler + Send + Sync + 'static,
+  {
+    self.err_handler = Some(Arc::new(handler));
+    self
+  }
+
+  /// Status code of validation errors, 404 by default
+  pub fn validation_status(mut self, status: StatusCode) -> Self {
+    self.validation_status = Some(status);
+    self
+  }
+
+  /// Status code of path segments which cannot be deserialized, 404 by default
+  pub fn deserialization_status(mut self, status: StatusCode) -> Self {
+    self.deserialization_status = Some(status);
+    self
+  }
+
+  pub fn from_req(req: &HttpRequest) -> &Self {
+    req
+      .app_data::<Self>()
+      .or_else(|| req.app_data::<Data<Self>>().map(Data::get_ref))
+      .unwrap_or(&DEFAULT_CONFIG)
+  }
+
+  pub(crate) fn status(&self, error: &crate::error::Error) -> Option<StatusCode> {
+    match error.as_cause() {
+      ErrorCause::ValidationError(_) => self.validation_status,
+      ErrorCause::PathError(_) => self.deserialization_status,
+      _ => None,
+    }
+  }
+}
+
+const DEFAULT_CONFIG: PathConfig = PathConfig {
+  err_handler: None,
+  validation_status: None,
+  deserialization_status: None,
+};
+
+impl Default for PathConfig {
+  fn default() -> Self {
+    DEFAULT_CONFIG
+  }
 }
 
 #[cfg(test)]
 mod test {
-  use crate::web::{Path, PathConfig};
+  use crate::web::{GardeConfig, Path, PathConfig};
   use actix_http::StatusCode;
   use actix_web::error::InternalError;
   use actix_web::test::{TestRequest, call_service, init_service};
@@ -152,6 +232,33 @@ mod test {
     let req = TestRequest::post().uri("/30/").to_request();
     let resp = call_service(&app, req).await;
     assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let req = TestRequest::post().uri("/abc/").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+  }
+
+  #[tokio::test]
+  async fn test_path_status_policy() {
+    let app = init_service(
+      App::new()
+        .app_data(GardeConfig::default().validation_status(StatusCode::CONFLICT))
+        .app_data(
+          PathConfig::default()
+            .validation_status(StatusCode::BAD_REQUEST)
+            .deserialization_status(StatusCode::GONE),
+        )
+        .service(resource("/{age}/").route(post().to(test_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post().uri("/30/").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = TestRequest::post().uri("/abc/").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::GONE);
   }
 
   #[tokio::test]
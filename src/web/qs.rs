@@ -1,16 +1,26 @@
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
 
 use actix_web::dev::Payload;
 use actix_web::error::QueryPayloadError;
-use actix_web::{Error, FromRequest, HttpRequest, web};
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest};
 use derive_more::{AsRef, Deref, DerefMut, Display, From};
-use futures::future::{LocalBoxFuture, Ready, err, ok};
-use futures::{FutureExt, StreamExt};
+use futures::future::{Ready, err, ok, ready};
+use futures::{FutureExt, ready};
 use garde::Validate;
 use serde::de::DeserializeOwned;
 use serde_qs::Config;
 
+use crate::context::ContextFallback;
+use crate::error::Source;
+use crate::limits::Limits;
 use crate::validate_for_request;
+use crate::web::buffer::BufferedBody;
+use crate::web::config::{ErrorHandler, handle_error};
+use crate::web::{ExtractFut, GardeExtractor, ValidationErrorHandler};
 
 /// Drop in replacement for [serde_qs::actix::QsQuery](https://docs.rs/serde_qs/latest/serde_qs/actix/struct.QsQuery.html)
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Deref, DerefMut, AsRef, Display, From)]
@@ -22,6 +32,8 @@ impl<T> QsQuery<T> {
   }
 }
 
+impl_serialize!(QsQuery);
+
 impl<T: DeserializeOwned> QsQuery<T> {
   pub fn from_query(query_str: &str) -> Result<Self, QueryPayloadError> {
     serde_urlencoded::from_str::<T>(query_str)
@@ -33,25 +45,22 @@ impl<T: DeserializeOwned> QsQuery<T> {
 impl<T> FromRequest for QsQuery<T>
 where
   T: DeserializeOwned + Validate + 'static,
-  T::Context: Default,
+  T::Context: ContextFallback,
 {
   type Error = Error;
   type Future = Ready<Result<Self, Error>>;
 
   #[inline]
-  fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+  fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
     let req_copy = req.clone();
-    let qs_config = req.app_data::<QsQueryConfig>();
-    let error_handler = qs_config.and_then(|c| c.err_handler.clone());
-    let default_qs_config = Config::default();
-    let qs_config = qs_config.map(|config| &config.qs_config).unwrap_or(&default_qs_config);
-
-    qs_config
-      .deserialize_str::<T>(req.query_string())
-      .map_err(Into::into)
+    let error_handler = Self::err_handler(req);
+
+    Self::extract_inner(req, payload)
+      .into_inner()
+      .map_err(|e| e.with_origin(Source::QsQuery))
       .and_then(|data: T| {
         let req = req_copy;
-        validate_for_request(data, &req)
+        validate_for_request(data, &req, Source::QsQuery)
       })
       .map(|val| ok(QsQuery(val)))
       .unwrap_or_else(move |e| {
@@ -61,19 +70,41 @@ where
           req.path()
         );
 
-        let e = if let Some(error_handler) = error_handler {
-          (error_handler)(e, req)
-        } else {
-          e.into()
-        };
-
-        err(e)
+        err(handle_error::<T>(e, req, Source::QsQuery, error_handler))
       })
   }
 }
 
+impl<T> GardeExtractor for QsQuery<T>
+where
+  T: DeserializeOwned + Validate + 'static,
+{
+  type Inner = T;
+  type InnerFuture = Ready<Result<T, crate::error::Error>>;
+
+  const SOURCE: Source = Source::QsQuery;
+
+  fn extract_inner(req: &HttpRequest, _: &mut Payload) -> Self::InnerFuture {
+    let parser = QsQueryConfig::parser(req.app_data::<QsQueryConfig>());
+
+    ready(deserialize_qs(&parser, req.query_string().as_bytes()))
+  }
+
+  fn from_inner(inner: T) -> Self {
+    QsQuery(inner)
+  }
+
+  fn into_value(self) -> T {
+    self.0
+  }
+
+  fn err_handler(req: &HttpRequest) -> Option<&dyn ValidationErrorHandler> {
+    Some(req.app_data::<QsQueryConfig>()?.err_handler.as_deref()?)
+  }
+}
+
 /// Drop in replacement for [serde_qs::actix::QsForm](https://docs.rs/serde_qs/latest/serde_qs/actix/struct.QsForm.html)
-#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Deref, DerefMut, AsRef, Display, From)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deref, DerefMut, AsRef, Display, From)]
 pub struct QsForm<T>(T);
 
 impl<T> QsForm<T> {
@@ -82,62 +113,111 @@ impl<T> QsForm<T> {
   }
 }
 
+impl_serialize!(QsForm);
+
 impl<T> FromRequest for QsForm<T>
 where
   T: DeserializeOwned + Validate + 'static,
-  T::Context: Default,
+  T::Context: ContextFallback,
 {
   type Error = Error;
-  type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+  type Future = ExtractFut<Self>;
 
+  #[inline]
   fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
-    let mut stream = payload.take();
-    let req_copy = req.clone();
-    let req_copy2 = req.clone();
-    let query_config: QsQueryConfig = req
-      .app_data::<QsQueryConfig>()
-      .cloned()
-      .unwrap_or_else(QsQueryConfig::default)
-      .clone();
-
-    async move {
-      let mut bytes = web::BytesMut::new();
-
-      while let Some(item) = stream.next().await {
-        bytes.extend_from_slice(&item?);
+    ExtractFut::new(req, payload)
+  }
+}
+
+impl<T> GardeExtractor for QsForm<T>
+where
+  T: DeserializeOwned + Validate + 'static,
+{
+  type Inner = T;
+  type InnerFuture = QsFormBody<T>;
+
+  const SOURCE: Source = Source::QsForm;
+
+  fn extract_inner(req: &HttpRequest, payload: &mut Payload) -> Self::InnerFuture {
+    let config = req.app_data::<QsQueryConfig>();
+    let limit = form_limit(req);
+
+    let can_parse_form = match req.mime_type() {
+      Ok(Some(mime)) => {
+        mime.essence_str() == mime::APPLICATION_WWW_FORM_URLENCODED.essence_str()
+          || config
+            .and_then(|c| c.content_type.as_deref())
+            .is_some_and(|predicate| predicate(mime))
       }
+      _ => false,
+    };
 
-      query_config
-        .qs_config
-        .deserialize_bytes::<T>(&bytes)
-        .map_err(Into::into)
-        .and_then(|data: T| {
-          let req = req_copy;
-          validate_for_request(data, &req)
-        })
-        .map(|val| Ok(QsForm(val)))
-        .unwrap_or_else(|e| {
-          let e = if let Some(error_handler) = &query_config.err_handler {
-            (error_handler)(e, &req_copy2)
-          } else {
-            e.into()
-          };
-
-          Err(e)
-        })
+    QsFormBody {
+      body: BufferedBody::new(req, payload, Source::QsForm, can_parse_form, limit),
+      parser: QsQueryConfig::parser(config),
+      data: PhantomData,
     }
-    .boxed_local()
+  }
+
+  fn from_inner(inner: T) -> Self {
+    QsForm(inner)
+  }
+
+  fn into_value(self) -> T {
+    self.0
+  }
+
+  fn err_handler(req: &HttpRequest) -> Option<&dyn ValidationErrorHandler> {
+    Some(req.app_data::<QsQueryConfig>()?.err_handler.as_deref()?)
   }
 }
 
-type ActixErrorHandler = Option<Arc<dyn Fn(crate::error::Error, &HttpRequest) -> Error + Send + Sync>>;
+/// Buffers the body of a [`QsForm`] up to the [`QsQueryConfig`] limit, then deserializes it with `serde_qs`
+pub struct QsFormBody<T> {
+  body: BufferedBody,
+  parser: QsParser,
+  data: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> Future for QsFormBody<T> {
+  type Output = Result<T, crate::error::Error>;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let this = self.get_mut();
+    let body = ready!(this.body.poll_unpin(cx))?;
+
+    Poll::Ready(deserialize_qs(&this.parser, &body))
+  }
+}
+
+/// Settings of a `serde_qs` deserialization, copied out of the [`QsQueryConfig`]
+#[derive(Clone, Copy)]
+struct QsParser {
+  qs_config: Config,
+  strict: bool,
+  limits: Limits,
+}
+
+fn deserialize_qs<T: DeserializeOwned>(parser: &QsParser, input: &[u8]) -> Result<T, crate::error::Error> {
+  parser.limits.check_query_keys(input, parser.strict)?;
+  let qs_config = parser.limits.qs_config(parser.qs_config, parser.strict);
+  let deserializer = serde_qs::Deserializer::with_config(&qs_config, input)?;
+
+  parser.limits.deserialize(deserializer, Into::into)
+}
 
 /// Replacement for [serde_qs::actix::QsQueryConfig](https://docs.rs/serde_qs/latest/serde_qs/actix/struct.QsQueryConfig.html)
 /// Error handler must map from an `garde_actix_web::error::Error`
-#[derive(Clone, Default)]
+///
+/// Also applies to [`QsForm`], whose bodies are limited to 16kB and must be `application/x-www-form-urlencoded` by default.
+#[derive(Clone)]
 pub struct QsQueryConfig {
-  err_handler: ActixErrorHandler,
+  err_handler: Option<ErrorHandler>,
   qs_config: Config,
+  strict: bool,
+  limit: usize,
+  content_type: Option<Arc<dyn Fn(mime::Mime) -> bool + Send + Sync>>,
+  limits: Limits,
 }
 
 impl QsQueryConfig {
@@ -149,23 +229,111 @@ impl QsQueryConfig {
     self
   }
 
+  /// Error handler receiving the extractor metadata, see [`ValidationErrorHandler`]
+  pub fn handler<H>(mut self, handler: H) -> Self
+  where
+    H: ValidationErrorHandler + Send + Sync + 'static,
+  {
+    self.err_handler = Some(Arc::new(handler));
+    self
+  }
+
+  /// `serde_qs` settings, whose max depth is replaced by [`Limits::max_depth`] when set
+  ///
+  /// `serde_qs` does not expose the strict mode of its config: set [`QsQueryConfig::strict`] along a non strict one.
   pub fn qs_config(mut self, config: Config) -> Self {
     self.qs_config = config;
     self
   }
+
+  /// Strict mode of the `serde_qs` config, `true` by default as in `serde_qs`
+  ///
+  /// Encoded brackets only nest keys out of strict mode, which the [`Limits`] checks of the raw keys and the
+  /// `serde_qs` config built from [`Limits::max_depth`] follow.
+  pub fn strict(mut self, strict: bool) -> Self {
+    self.strict = strict;
+    self
+  }
+
+  /// Maximum size of [`QsForm`] bodies, larger ones are rejected with a 413
+  pub fn limit(mut self, limit: usize) -> Self {
+    self.limit = limit;
+    self
+  }
+
+  /// Accepts [`QsForm`] bodies of other content types, rejected with a 415 otherwise
+  pub fn content_type<F>(mut self, predicate: F) -> Self
+  where
+    F: Fn(mime::Mime) -> bool + Send + Sync + 'static,
+  {
+    self.content_type = Some(Arc::new(predicate));
+    self
+  }
+
+  /// Structural limits checked while deserializing query strings and [`QsForm`] bodies, see [`Limits`]
+  ///
+  /// The field count, key depth and duplicate keys are checked on the raw keys, before `serde_qs` builds its tree,
+  /// and the max depth also bounds the `serde_qs` parser. Collection and string lengths can only be checked once the
+  /// tree is built, as `serde_qs` parses the whole input up front.
+  pub fn limits(mut self, limits: Limits) -> Self {
+    self.limits = limits;
+    self
+  }
+
+  /// Deserialization settings of the config registered for the request, if any
+  fn parser(config: Option<&Self>) -> QsParser {
+    config.map_or_else(
+      || QsParser {
+        qs_config: Config::default(),
+        strict: true,
+        limits: Limits::NONE,
+      },
+      |config| QsParser {
+        qs_config: config.qs_config,
+        strict: config.strict,
+        limits: config.limits,
+      },
+    )
+  }
+}
+
+const DEFAULT_FORM_LIMIT: usize = 16_384; // 2^14 bytes (~16kB)
+
+/// Body limit of [`QsForm`], from the registered [`QsQueryConfig`]
+pub(crate) fn form_limit(req: &HttpRequest) -> usize {
+  req.app_data::<QsQueryConfig>().map_or(DEFAULT_FORM_LIMIT, |c| c.limit)
+}
+
+impl Default for QsQueryConfig {
+  fn default() -> Self {
+    QsQueryConfig {
+      err_handler: None,
+      qs_config: Config::default(),
+      strict: true,
+      limit: DEFAULT_FORM_LIMIT,
+      content_type: None,
+      limits: Limits::NONE,
+    }
+  }
 }
 
 #[cfg(test)]
 mod test {
   use actix_http::StatusCode;
+  use actix_http::header::CONTENT_TYPE;
   use actix_web::error::InternalError;
-  use actix_web::test::{TestRequest, call_service, init_service};
+  use actix_web::test::{TestRequest, call_service, init_service, read_body};
   use actix_web::web::{post, resource};
-  use actix_web::{App, HttpResponse};
+  use actix_web::{App, HttpResponse, ResponseError};
   use garde::Validate;
   use serde::{Deserialize, Serialize};
+  use serde_qs::Config;
 
-  use crate::web::{Form, FormConfig, QsQuery, QsQueryConfig};
+  use crate::error::{Error, ErrorCause};
+  use crate::limits::Limits;
+  use crate::web::qs::deserialize_qs;
+  use crate::web::{Form, FormConfig, QsForm, QsQuery, QsQueryConfig};
+  use std::collections::HashMap;
 
   #[derive(Debug, PartialEq, Validate, Serialize, Deserialize)]
   struct QueryData {
@@ -213,6 +381,10 @@ mod test {
     HttpResponse::Ok().finish()
   }
 
+  async fn test_qs_form_handler(form: QsForm<FormData>) -> HttpResponse {
+    HttpResponse::Ok().body(form.into_inner().age.to_string())
+  }
+
   async fn test_handler_with_context(_: QsQuery<QueryDataWithContext>) -> HttpResponse {
     HttpResponse::Ok().finish()
   }
@@ -376,4 +548,167 @@ mod test {
     let resp = call_service(&app, req).await;
     assert_eq!(resp.status(), StatusCode::OK);
   }
+
+  #[tokio::test]
+  async fn test_qs_form_limits() {
+    let app = init_service(App::new().service(resource("/").route(post().to(test_qs_form_handler)))).await;
+
+    let req = TestRequest::post()
+      .uri("/")
+      .set_form(&FormData { age: 24 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(read_body(resp).await, "24");
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "application/json"))
+      .set_payload("age=24")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let req = TestRequest::post().uri("/").set_payload("age=24").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+      .set_payload(format!("age=24&padding={}", "a".repeat(16_384)))
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+  }
+
+  #[tokio::test]
+  async fn test_qs_form_limits_custom_config() {
+    let app = init_service(
+      App::new()
+        .app_data(
+          QsQueryConfig::default()
+            .limit(8)
+            .content_type(|mime| mime == mime::TEXT_PLAIN)
+            .error_handler(|err, _req| {
+              let status = err.status_code();
+              InternalError::from_response(err, HttpResponse::Conflict().body(status.as_u16().to_string())).into()
+            }),
+        )
+        .service(resource("/").route(post().to(test_qs_form_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "text/plain"))
+      .set_payload("age=24")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "application/json"))
+      .set_payload("age=24")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+    assert_eq!(read_body(resp).await, "415");
+
+    let req = TestRequest::post()
+      .uri("/")
+      .insert_header((CONTENT_TYPE, "text/plain"))
+      .set_payload("age=24&a=b")
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+    assert_eq!(read_body(resp).await, "413");
+  }
+
+  #[tokio::test]
+  async fn test_qs_limits() {
+    let app = init_service(
+      App::new()
+        .app_data(
+          QsQueryConfig::default()
+            .limits(
+              Limits::default()
+                .max_depth(2)
+                .max_collection_length(2)
+                .max_fields(4)
+                .reject_duplicate_keys(true),
+            )
+            .error_handler(|err, _req| {
+              let kind = format!("{:?}", err.kind());
+              InternalError::from_response(err, HttpResponse::BadRequest().body(kind)).into()
+            }),
+        )
+        .service(resource("/query").route(post().to(test_handler)))
+        .service(resource("/form").route(post().to(test_qs_form_handler))),
+    )
+    .await;
+
+    let cases = [
+      ("/query?age=24&user[name]=netwo", StatusCode::OK, ""),
+      ("/query?age=30", StatusCode::BAD_REQUEST, "Validation"),
+      (
+        "/query?age=24&user[address][city]=paris",
+        StatusCode::BAD_REQUEST,
+        "DepthLimit",
+      ),
+      (
+        "/query?age=24&a=1&b=2",
+        StatusCode::BAD_REQUEST,
+        "CollectionLengthLimit",
+      ),
+      ("/query?age=1&age=2", StatusCode::BAD_REQUEST, "DuplicateKey"),
+      (
+        "/query?age=24&a=1&b=2&c=3&d=4",
+        StatusCode::BAD_REQUEST,
+        "FieldCountLimit",
+      ),
+    ];
+    for (uri, status, kind) in cases {
+      let req = TestRequest::post().uri(uri).to_request();
+      let resp = call_service(&app, req).await;
+      assert_eq!(resp.status(), status, "{uri}");
+      assert_eq!(read_body(resp).await, kind, "{uri}");
+    }
+
+    let cases = [
+      ("age=24&tags[0]=a&tags[1]=b", StatusCode::OK, "24"),
+      ("age=24&tags[]=a&tags[]=b", StatusCode::OK, "24"),
+      ("age=24&age=25", StatusCode::BAD_REQUEST, "DuplicateKey"),
+      ("age=24&tags[0]=a&tags[0]=b", StatusCode::BAD_REQUEST, "DuplicateKey"),
+      (
+        "age=24&tags[0]=a&tags[1]=b&tags[2]=c",
+        StatusCode::BAD_REQUEST,
+        "CollectionLengthLimit",
+      ),
+    ];
+    for (payload, status, body) in cases {
+      let req = TestRequest::post()
+        .uri("/form")
+        .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+        .set_payload(payload)
+        .to_request();
+      let resp = call_service(&app, req).await;
+      assert_eq!(resp.status(), status, "{payload}");
+      assert_eq!(read_body(resp).await, body, "{payload}");
+    }
+  }
+
+  #[test]
+  fn test_qs_strict_mode() {
+    let input = b"a%5Bb%5D%5Bc%5D=1";
+    let config = QsQueryConfig::default().limits(Limits::default().max_depth(2));
+    let parser = QsQueryConfig::parser(Some(&config));
+    assert!(deserialize_qs::<HashMap<String, String>>(&parser, input).is_ok());
+
+    let config = config.qs_config(Config::new(5, false)).strict(false);
+    let parser = QsQueryConfig::parser(Some(&config));
+    let res = deserialize_qs::<HashMap<String, String>>(&parser, input).map_err(Error::into_cause);
+    assert!(matches!(res, Err(ErrorCause::DepthLimit(2))));
+  }
 }
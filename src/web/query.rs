@@ -1,9 +1,14 @@
+use crate::context::ContextFallback;
+use crate::error::Source;
+use crate::limits::Limits;
 use crate::validate_for_request;
+use crate::web::config::{ErrorHandler, handle_error};
+use crate::web::{GardeExtractor, ValidationErrorHandler};
 use actix_web::dev::Payload;
 use actix_web::error::QueryPayloadError;
 use actix_web::{Error, FromRequest, HttpRequest};
 use derive_more::{AsRef, Deref, DerefMut, Display, From};
-use futures::future::{Ready, err, ok};
+use futures::future::{Ready, err, ok, ready};
 use garde::Validate;
 use serde::de::DeserializeOwned;
 use std::sync::Arc;
@@ -18,6 +23,8 @@ impl<T> Query<T> {
   }
 }
 
+impl_serialize!(Query);
+
 impl<T: DeserializeOwned> Query<T> {
   pub fn from_query(query_str: &str) -> Result<Self, QueryPayloadError> {
     serde_urlencoded::from_str::<T>(query_str)
@@ -29,24 +36,22 @@ impl<T: DeserializeOwned> Query<T> {
 impl<T> FromRequest for Query<T>
 where
   T: DeserializeOwned + Validate + 'static,
-  T::Context: Default,
+  T::Context: ContextFallback,
 {
   type Error = Error;
   type Future = Ready<Result<Self, Error>>;
 
   #[inline]
-  fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+  fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
     let req_copy = req.clone();
-    let error_handler = req.app_data::<QueryConfig>().and_then(|c| c.err_handler.clone());
+    let error_handler = Self::err_handler(req);
 
-    serde_urlencoded::from_str::<T>(req.query_string())
-      .map_err(|e| {
-        let e = QueryPayloadError::Deserialize(e);
-        crate::error::Error::QueryPayloadError(e)
-      })
+    Self::extract_inner(req, payload)
+      .into_inner()
+      .map_err(|e| e.with_origin(Source::Query))
       .and_then(|data: T| {
         let req = req_copy;
-        validate_for_request(data, &req)
+        validate_for_request(data, &req, Source::Query)
       })
       .map(|val| ok(Query(val)))
       .unwrap_or_else(move |e| {
@@ -56,23 +61,49 @@ where
           req.path()
         );
 
-        let e = if let Some(error_handler) = error_handler {
-          (error_handler)(e, req)
-        } else {
-          e.into()
-        };
-
-        err(e)
+        err(handle_error::<T>(e, req, Source::Query, error_handler))
       })
   }
 }
 
+impl<T> GardeExtractor for Query<T>
+where
+  T: DeserializeOwned + Validate + 'static,
+{
+  type Inner = T;
+  type InnerFuture = Ready<Result<T, crate::error::Error>>;
+
+  const SOURCE: Source = Source::Query;
+
+  fn extract_inner(req: &HttpRequest, _: &mut Payload) -> Self::InnerFuture {
+    let limits = req.app_data::<QueryConfig>().map(|c| c.limits).unwrap_or_default();
+    let deserializer = serde_urlencoded::Deserializer::new(form_urlencoded::parse(req.query_string().as_bytes()));
+
+    ready(limits.deserialize(deserializer, |e| {
+      let e = QueryPayloadError::Deserialize(e);
+      crate::error::Error::from(e)
+    }))
+  }
+
+  fn from_inner(inner: T) -> Self {
+    Query(inner)
+  }
+
+  fn into_value(self) -> T {
+    self.0
+  }
+
+  fn err_handler(req: &HttpRequest) -> Option<&dyn ValidationErrorHandler> {
+    Some(req.app_data::<QueryConfig>()?.err_handler.as_deref()?)
+  }
+}
+
 /// Replacement for [actix_web::web::QueryConfig](https://docs.rs/actix-web/latest/actix_web/web/struct.QueryConfig.html)
 /// Error handler must map from an `garde_actix_web::error::Error`
 #[derive(Clone, Default)]
 pub struct QueryConfig {
-  #[allow(clippy::type_complexity)]
-  pub(crate) err_handler: Option<Arc<dyn Fn(crate::error::Error, &HttpRequest) -> Error + Send + Sync>>,
+  pub(crate) err_handler: Option<ErrorHandler>,
+  pub(crate) limits: Limits,
 }
 
 impl QueryConfig {
@@ -83,14 +114,32 @@ impl QueryConfig {
     self.err_handler = Some(Arc::new(f));
     self
   }
+
+  /// Error handler receiving the extractor metadata, see [`ValidationErrorHandler`]
+  pub fn handler<H>(mut self, handler: H) -> Self
+  where
+    H: ValidationErrorHandler + Send + Sync + 'static,
+  {
+    self.err_handler = Some(Arc::new(handler));
+    self
+  }
+
+  /// Structural limits checked while deserializing the query string, see [`Limits`]
+  pub fn limits(mut self, limits: Limits) -> Self {
+    self.limits = limits;
+    self
+  }
 }
 
 #[cfg(test)]
 mod test {
-  use crate::web::{Query, QueryConfig};
+  use crate::error::{ErrorFormat, ErrorKind, ProblemConfig};
+  use crate::limits::Limits;
+  use crate::web::{GardeConfig, Query, QueryConfig};
   use actix_http::StatusCode;
+  use actix_http::header::CONTENT_TYPE;
   use actix_web::error::InternalError;
-  use actix_web::test::{TestRequest, call_service, init_service};
+  use actix_web::test::{TestRequest, call_service, init_service, read_body, read_body_json};
   use actix_web::web::{post, resource};
   use actix_web::{App, HttpResponse};
   use garde::Validate;
@@ -163,6 +212,39 @@ mod test {
     assert_eq!(resp.status(), StatusCode::CONFLICT);
   }
 
+  #[tokio::test]
+  async fn test_query_validation_problem_error_format() {
+    let problem_config = ProblemConfig::default()
+      .type_uri(ErrorKind::Validation, "https://example.com/problems/validation")
+      .instance(true);
+    let app = init_service(
+      App::new()
+        .app_data(GardeConfig::default().error_format(ErrorFormat::Problem(problem_config)))
+        .service(resource("/").route(post().to(test_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post().uri("/?age=30").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(resp.headers().get(CONTENT_TYPE).unwrap(), "application/problem+json");
+
+    let body: serde_json::Value = read_body_json(resp).await;
+    assert_eq!(body["type"], "https://example.com/problems/validation");
+    assert_eq!(body["status"], 400);
+    assert_eq!(body["instance"], "/");
+    assert_eq!(body["errors"][0]["path"], "age");
+
+    let req = TestRequest::post().uri("/?age=abc").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let body: serde_json::Value = read_body_json(resp).await;
+    assert_eq!(body["type"], "about:blank");
+    assert_eq!(body["title"], "Bad Request");
+    assert!(body.get("errors").is_none());
+  }
+
   #[tokio::test]
   async fn test_query_validation_with_context() {
     let number_context = NumberContext { min: 25 };
@@ -194,4 +276,44 @@ mod test {
     let resp = call_service(&app, req).await;
     assert_eq!(resp.status(), StatusCode::OK);
   }
+
+  #[tokio::test]
+  async fn test_query_limits() {
+    let app = init_service(
+      App::new()
+        .app_data(
+          QueryConfig::default()
+            .limits(
+              Limits::default()
+                .max_fields(3)
+                .max_string_length(5)
+                .reject_duplicate_keys(true),
+            )
+            .error_handler(|err, _req| {
+              let kind = format!("{:?}", err.kind());
+              InternalError::from_response(err, HttpResponse::BadRequest().body(kind)).into()
+            }),
+        )
+        .service(resource("/").route(post().to(test_handler))),
+    )
+    .await;
+
+    let cases = [
+      ("/?age=24&name=netwo", StatusCode::OK, ""),
+      ("/?age=30", StatusCode::BAD_REQUEST, "Validation"),
+      ("/?age=24&a=1&b=2&c=3", StatusCode::BAD_REQUEST, "FieldCountLimit"),
+      (
+        "/?age=24&name=netwo+netwo",
+        StatusCode::BAD_REQUEST,
+        "StringLengthLimit",
+      ),
+      ("/?age=24&age=25", StatusCode::BAD_REQUEST, "DuplicateKey"),
+    ];
+    for (uri, status, kind) in cases {
+      let req = TestRequest::post().uri(uri).to_request();
+      let resp = call_service(&app, req).await;
+      assert_eq!(resp.status(), status, "{uri}");
+      assert_eq!(read_body(resp).await, kind, "{uri}");
+    }
+  }
 }
@@ -0,0 +1,165 @@
+use crate::check_for_request;
+use crate::context::ContextFallback;
+use crate::error::Source;
+use crate::web::{GardeConfig, Json};
+use actix_web::body::EitherBody;
+use actix_web::{HttpRequest, HttpResponse, Responder};
+use derive_more::{AsRef, Deref, DerefMut, Display, From};
+use garde::Validate;
+use serde::Serialize;
+use std::any::type_name;
+
+/// JSON responder validating the outgoing value before serializing it
+///
+/// The context is looked up as for extractors. Invalid values are handled according to
+/// [`GardeConfig::response_validation`], by default with an empty 500 response.
+///
+/// ```rust
+/// use garde::Validate;
+/// use garde_actix_web::web::ValidatedJson;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize, Validate)]
+/// struct User {
+///   #[garde(length(min = 1))]
+///   name: String,
+/// }
+///
+/// async fn get_user() -> ValidatedJson<User> {
+///   ValidatedJson(User { name: "netwo".to_string() })
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Deref, DerefMut, AsRef, Display, From)]
+pub struct ValidatedJson<T>(pub T);
+
+impl<T> ValidatedJson<T> {
+  pub fn into_inner(self) -> T {
+    self.0
+  }
+}
+
+impl_serialize!(ValidatedJson);
+
+impl<T> Responder for ValidatedJson<T>
+where
+  T: Serialize + Validate + 'static,
+  T::Context: ContextFallback,
+{
+  type Body = EitherBody<String>;
+
+  fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+    let error = match check_for_request(&self.0, req, Source::Json) {
+      Ok(None) => return Json(self.0).respond_to(req),
+      Ok(Some(report)) => crate::error::Error::new(report, Source::Json),
+      Err(e) => e,
+    };
+
+    match GardeConfig::from_req(req).response_validation_mode() {
+      ResponseValidation::Log => {
+        log::warn!(
+          "Invalid {} response: {}. \
+                 Request path: {}",
+          type_name::<T>(),
+          error,
+          req.path()
+        );
+        Json(self.0).respond_to(req)
+      }
+      #[cfg(debug_assertions)]
+      #[allow(clippy::panic)]
+      ResponseValidation::Panic => panic!("Invalid {} response: {}", type_name::<T>(), error),
+      _ => {
+        log::error!(
+          "Invalid {} response: {}. \
+                 Request path: {}",
+          type_name::<T>(),
+          error,
+          req.path()
+        );
+        HttpResponse::InternalServerError().finish().map_into_right_body()
+      }
+    }
+  }
+}
+
+/// Behaviour of [`ValidatedJson`] responses holding invalid data, set through
+/// [`GardeConfig::response_validation`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResponseValidation {
+  /// Logs the error and answers with an empty 500
+  #[default]
+  Reject,
+  /// Logs the error and sends the response anyway
+  Log,
+  /// Panics in debug builds, behaves as `Reject` in release builds
+  Panic,
+}
+
+#[cfg(test)]
+mod test {
+  use crate::web::{GardeConfig, ResponseValidation, ValidatedJson};
+  use actix_http::StatusCode;
+  use actix_web::test::{TestRequest, call_service, init_service, read_body};
+  use actix_web::web::{get, resource};
+  use actix_web::{App, HttpRequest, Responder};
+  use garde::Validate;
+  use serde::Serialize;
+
+  #[derive(Debug, Validate, Serialize)]
+  struct User {
+    #[garde(length(min = 1))]
+    name: String,
+  }
+
+  async fn test_handler(req: HttpRequest) -> ValidatedJson<User> {
+    ValidatedJson(User {
+      name: req.match_info().get("name").unwrap_or_default().to_string(),
+    })
+  }
+
+  #[tokio::test]
+  async fn test_validated_json_responder() {
+    let app = init_service(
+      App::new()
+        .service(resource("/users/").route(get().to(test_handler)))
+        .service(resource("/users/{name}").route(get().to(test_handler))),
+    )
+    .await;
+
+    let req = TestRequest::get().uri("/users/netwo").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(read_body(resp).await, "{\"name\":\"netwo\"}");
+
+    let req = TestRequest::get().uri("/users/").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(read_body(resp).await, "");
+  }
+
+  #[tokio::test]
+  async fn test_validated_json_responder_log_mode() {
+    let app = init_service(
+      App::new()
+        .app_data(GardeConfig::default().response_validation(ResponseValidation::Log))
+        .service(resource("/users/").route(get().to(test_handler))),
+    )
+    .await;
+
+    let req = TestRequest::get().uri("/users/").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(read_body(resp).await, "{\"name\":\"\"}");
+  }
+
+  #[cfg(debug_assertions)]
+  #[test]
+  #[should_panic(expected = "Invalid")]
+  fn test_validated_json_responder_panic_mode() {
+    let req = TestRequest::default()
+      .app_data(GardeConfig::default().response_validation(ResponseValidation::Panic))
+      .to_http_request();
+
+    let _ = ValidatedJson(User { name: String::new() }).respond_to(&req);
+  }
+}
@@ -0,0 +1,255 @@
+use crate::async_validate::check_async_for_request;
+use crate::context::ContextFallback;
+use crate::context_for_request;
+use crate::error::Location;
+use crate::web::GardeExtractor;
+use crate::web::config::handle_error;
+use actix_web::dev::Payload;
+use actix_web::{Error, FromRequest, HttpRequest};
+use futures::FutureExt;
+use futures::future::LocalBoxFuture;
+use garde::{Unvalidated, Validate};
+use serde::Serialize;
+use std::{fmt, ops};
+
+/// Extractor yielding a [`garde::Valid`], proving at the type level that the data was validated
+///
+/// Wraps any extractor of this crate: `Valid<Json<T>>`, `Valid<Path<T>>`, `Valid<Header<T>>`...
+///
+/// ```rust
+/// use actix_web::HttpResponse;
+/// use garde::Validate;
+/// use garde_actix_web::web::{Json, Valid};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Validate)]
+/// struct NewUser {
+///   #[garde(length(min = 3))]
+///   username: String,
+/// }
+///
+/// fn create_user(user: garde::Valid<NewUser>) {}
+///
+/// async fn handler(user: Valid<Json<NewUser>>) -> HttpResponse {
+///   create_user(user.into_inner());
+///   HttpResponse::Created().finish()
+/// }
+/// ```
+pub struct Valid<E: GardeExtractor>(garde::Valid<E::Inner>);
+
+impl<E: GardeExtractor> Valid<E> {
+  pub fn into_inner(self) -> garde::Valid<E::Inner> {
+    self.0
+  }
+}
+
+impl<E: GardeExtractor> ops::Deref for Valid<E> {
+  type Target = garde::Valid<E::Inner>;
+
+  fn deref(&self) -> &garde::Valid<E::Inner> {
+    &self.0
+  }
+}
+
+impl<E: GardeExtractor> fmt::Debug for Valid<E>
+where
+  E::Inner: fmt::Debug,
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_tuple("Valid").field(&self.0).finish()
+  }
+}
+
+impl<E: GardeExtractor> Clone for Valid<E>
+where
+  E::Inner: Clone,
+{
+  fn clone(&self) -> Self {
+    Valid(self.0.clone())
+  }
+}
+
+impl<E: GardeExtractor> fmt::Display for Valid<E>
+where
+  E::Inner: fmt::Display,
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt::Display::fmt(&*self.0, f)
+  }
+}
+
+impl<E: GardeExtractor> From<garde::Valid<E::Inner>> for Valid<E> {
+  fn from(valid: garde::Valid<E::Inner>) -> Self {
+    Valid(valid)
+  }
+}
+
+impl<E: GardeExtractor> Serialize for Valid<E>
+where
+  E::Inner: Serialize,
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: serde::Serializer,
+  {
+    (*self.0).serialize(serializer)
+  }
+}
+
+impl<E> FromRequest for Valid<E>
+where
+  E: GardeExtractor + 'static,
+  <E::Inner as Validate>::Context: ContextFallback,
+{
+  type Error = Error;
+  type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+  #[inline]
+  fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+    let req = req.clone();
+    let inner = E::extract_inner(&req, payload);
+
+    async move {
+      let res = async {
+        let data = inner.await.map_err(|e| e.with_origin(E::SOURCE))?;
+        let context = context_for_request::<E::Inner>(&req, E::SOURCE)?;
+        let valid = Unvalidated::new(data)
+          .validate_with(&context)
+          .map_err(|report| crate::error::Error::new(report, E::SOURCE))?;
+
+        if E::SOURCE.location() == Location::Body {
+          if let Some(report) = check_async_for_request(&*valid, &req).await {
+            return Err(crate::error::Error::new(report, E::SOURCE));
+          }
+        }
+
+        Ok(Valid(valid))
+      };
+
+      res.await.map_err(|e| {
+        log::debug!(
+          "Failed during {} extractor validation. \
+                         Request path: {}",
+          E::SOURCE,
+          req.path()
+        );
+
+        handle_error::<E::Inner>(e, &req, E::SOURCE, E::err_handler(&req))
+      })
+    }
+    .boxed_local()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::web::{Header, Json, Path, Valid};
+  use actix_http::StatusCode;
+  use actix_http::header::{HeaderName, HeaderValue, InvalidHeaderValue, TryIntoHeaderValue};
+  use actix_web::error::ParseError;
+  use actix_web::test::{TestRequest, call_service, init_service};
+  use actix_web::web::{get, post, resource};
+  use actix_web::{App, HttpMessage, HttpResponse};
+  use garde::Validate;
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Debug, PartialEq, Validate, Serialize, Deserialize)]
+  struct AgeData {
+    #[garde(range(min = 18, max = 28))]
+    age: u8,
+  }
+
+  #[derive(Debug, Validate)]
+  struct AgeHeader(#[garde(range(min = 18, max = 28))] u8);
+
+  impl TryIntoHeaderValue for AgeHeader {
+    type Error = InvalidHeaderValue;
+
+    fn try_into_value(self) -> Result<HeaderValue, Self::Error> {
+      HeaderValue::from_str(&self.0.to_string())
+    }
+  }
+
+  impl actix_http::header::Header for AgeHeader {
+    fn name() -> HeaderName {
+      HeaderName::from_static("age")
+    }
+
+    fn parse<M: HttpMessage>(msg: &M) -> Result<Self, ParseError> {
+      msg
+        .headers()
+        .get(Self::name())
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+        .map(AgeHeader)
+        .ok_or(ParseError::Header)
+    }
+  }
+
+  fn older_than(data: &garde::Valid<AgeData>, age: u8) -> bool {
+    data.age > age
+  }
+
+  async fn test_json_handler(data: Valid<Json<AgeData>>) -> HttpResponse {
+    HttpResponse::Ok().body(older_than(&data, 20).to_string())
+  }
+
+  async fn test_path_handler(data: Valid<Path<AgeData>>) -> HttpResponse {
+    HttpResponse::Ok().body(data.into_inner().into_inner().age.to_string())
+  }
+
+  async fn test_header_handler(data: Valid<Header<AgeHeader>>) -> HttpResponse {
+    HttpResponse::Ok().body(data.into_inner().into_inner().0.to_string())
+  }
+
+  #[tokio::test]
+  async fn test_valid_extractors() {
+    let app = init_service(
+      App::new()
+        .service(resource("/json").route(post().to(test_json_handler)))
+        .service(resource("/path/{age}").route(get().to(test_path_handler)))
+        .service(resource("/header").route(get().to(test_header_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post()
+      .uri("/json")
+      .set_json(AgeData { age: 24 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = TestRequest::post()
+      .uri("/json")
+      .set_json(AgeData { age: 30 })
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = TestRequest::get().uri("/path/24").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = TestRequest::get().uri("/path/30").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let req = TestRequest::get()
+      .uri("/header")
+      .insert_header(("age", "24"))
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = TestRequest::get()
+      .uri("/header")
+      .insert_header(("age", "30"))
+      .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = TestRequest::get().uri("/header").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+  }
+}
@@ -0,0 +1,149 @@
+use crate::check_for_request;
+use crate::context::ContextFallback;
+use crate::error::Source;
+use crate::web::config::handle_error;
+use actix_web::dev::Payload;
+use actix_web::{Error, FromRequest, HttpRequest};
+use derive_more::{AsRef, Deref, DerefMut, Display, From};
+use futures::FutureExt;
+use futures::future::LocalBoxFuture;
+use garde::Validate;
+use std::any::type_name;
+use std::ops;
+
+/// Validates the output of any extractor dereferencing to a `garde::Validate` type
+///
+/// Brings garde to extractors not mirrored by this crate (`actix_web::web::ReqData`, `actix_web_lab::extract::Json`,
+/// custom extractors...). Errors of the wrapped extractor are returned as is, validation errors go through the
+/// app wide [`GardeConfig`](crate::web::GardeConfig).
+///
+/// ```rust
+/// use actix_web::HttpResponse;
+/// use actix_web::web::ReqData;
+/// use garde::Validate;
+/// use garde_actix_web::web::Validated;
+///
+/// #[derive(Clone, Validate)]
+/// struct Tenant {
+///   #[garde(length(min = 1))]
+///   name: String,
+/// }
+///
+/// async fn handler(tenant: Validated<ReqData<Tenant>>) -> HttpResponse {
+///   HttpResponse::Ok().body(tenant.name.clone())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Deref, DerefMut, AsRef, Display, From)]
+pub struct Validated<E>(pub E);
+
+impl<E> Validated<E> {
+  pub fn into_inner(self) -> E {
+    self.0
+  }
+}
+
+impl_serialize!(Validated);
+
+impl<E> FromRequest for Validated<E>
+where
+  E: FromRequest + ops::Deref + 'static,
+  E::Target: Validate + Sized + 'static,
+  <E::Target as Validate>::Context: ContextFallback,
+{
+  type Error = Error;
+  type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+  #[inline]
+  fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+    let req = req.clone();
+    let inner = E::from_request(&req, payload);
+
+    async move {
+      let extracted = inner.await.map_err(Into::into)?;
+
+      let res = match check_for_request(&*extracted, &req, Source::Extractor) {
+        Ok(None) => Ok(Validated(extracted)),
+        Ok(Some(report)) => Err(crate::error::Error::new(report, Source::Extractor)),
+        Err(e) => Err(e),
+      };
+
+      res.map_err(|e| {
+        log::debug!(
+          "Failed during {} validation. \
+                         Request path: {}",
+          type_name::<E>(),
+          req.path()
+        );
+
+        handle_error::<E::Target>(e, &req, Source::Extractor, None)
+      })
+    }
+    .boxed_local()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::web::{GardeConfig, Validated};
+  use actix_http::StatusCode;
+  use actix_web::error::InternalError;
+  use actix_web::test::{TestRequest, call_service, init_service};
+  use actix_web::web::{Json, ReqData, post, resource};
+  use actix_web::{App, HttpMessage, HttpResponse};
+  use garde::Validate;
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Clone, Debug, PartialEq, Validate, Serialize, Deserialize)]
+  struct AgeData {
+    #[garde(range(min = 18, max = 28))]
+    age: u8,
+  }
+
+  async fn test_json_handler(_: Validated<Json<AgeData>>) -> HttpResponse {
+    HttpResponse::Ok().finish()
+  }
+
+  async fn test_req_data_handler(_: Validated<ReqData<AgeData>>) -> HttpResponse {
+    HttpResponse::Ok().finish()
+  }
+
+  #[tokio::test]
+  async fn test_validated_actix_json() {
+    let app = init_service(App::new().service(resource("/").route(post().to(test_json_handler)))).await;
+
+    let req = TestRequest::post().uri("/").set_json(AgeData { age: 24 }).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = TestRequest::post().uri("/").set_json(AgeData { age: 30 }).to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = TestRequest::post().uri("/").set_payload("{\"age\": -1}").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+  }
+
+  #[tokio::test]
+  async fn test_validated_req_data_with_global_handler() {
+    let app = init_service(
+      App::new()
+        .app_data(
+          GardeConfig::default()
+            .error_handler(|err, _req| InternalError::from_response(err, HttpResponse::Conflict().finish()).into()),
+        )
+        .service(resource("/").route(post().to(test_req_data_handler))),
+    )
+    .await;
+
+    let req = TestRequest::post().uri("/").to_request();
+    req.extensions_mut().insert(AgeData { age: 24 });
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = TestRequest::post().uri("/").to_request();
+    req.extensions_mut().insert(AgeData { age: 30 });
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+  }
+}